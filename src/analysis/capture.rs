@@ -0,0 +1,108 @@
+use crate::analysis::resolve::SymbolTable;
+use crate::ast::fun::FunExpression;
+use crate::ast::node::{Expression, ExpressionKind, Statement, StatementKind};
+use crate::ast::visit::{MutVisitor, walk_expression_mut, walk_program_mut, walk_statement_mut};
+use crate::parser::span::Span;
+
+struct Capture<'a> {
+    symbols:&'a SymbolTable,
+    //the funs being walked with what they capture so far, innermost last
+    funs:Vec<(Span,Vec<String>)>,
+}
+
+//fills FunExpression::captures with the outer local variables each function uses,
+//as resolved by analysis::resolve. a variable is outer to every fun around its use
+//that does not also hold its declaration, top level declarations are globals and
+//never captured
+pub fn resolve(stems:&mut [Statement],symbols:&SymbolTable) {
+    let mut c = Capture {
+        symbols,
+        funs:vec![],
+    };
+    walk_program_mut(&mut c,stems);
+}
+
+impl Capture<'_> {
+    fn use_name(&mut self,span:Span) {
+        let symbol = match self.symbols.binding(span) {
+            Some(symbol) if symbol.scope != 0 => symbol,
+            _ => return
+        };
+        for (fun,captures) in &mut self.funs {
+            let inside = fun.start <= symbol.span.start && symbol.span.end <= fun.end;
+            if !inside && !captures.contains(&symbol.name) {
+                captures.push(symbol.name.clone());
+            }
+        }
+    }
+    //span is the fun statement or expression, defaults are evaluated where the fun is declared
+    fn fun(&mut self,f:&mut FunExpression,span:Span) {
+        for exp in f.param_exp.iter_mut().flatten() {
+            self.visit_expression(exp);
+        }
+        self.funs.push((span,vec![]));
+        walk_program_mut(self,&mut f.body);
+        f.captures = self.funs.pop().map(|(_,captures)| captures).unwrap_or_default();
+    }
+}
+
+impl MutVisitor for Capture<'_> {
+    fn visit_statement(&mut self,stem:&mut Statement) {
+        let span = stem.span;
+        match &mut stem.kind {
+            StatementKind::Fun(f) => self.fun(f,span),
+            _ => walk_statement_mut(self,stem)
+        }
+    }
+    fn visit_expression(&mut self,exp:&mut Expression) {
+        let span = exp.span;
+        match &mut exp.kind {
+            ExpressionKind::Ident(_) => self.use_name(span),
+            ExpressionKind::Fun(f) => self.fun(f,span),
+            _ => walk_expression_mut(self,exp)
+        }
+    }
+}
+
+#[test]
+fn test_capture() {
+    let path = "./src/script/16_parser.kz";
+    let mut stems = super::fixture(path);
+    let (symbols,_) = crate::analysis::resolve::resolve(path,&stems);
+    resolve(&mut stems,&symbols);
+    let outer = match &stems[1].kind {
+        StatementKind::Fun(f) => f,
+        _ => panic!("expected fun")
+    };
+    assert!(outer.captures.is_empty());
+    let lambda = |stem:&Statement| -> Vec<String> {
+        match &stem.kind {
+            StatementKind::Let(l) => match &l.exp {
+                Some(Expression { kind:ExpressionKind::Fun(f), .. }) => f.captures.clone(),
+                _ => panic!("expected lambda")
+            },
+            _ => panic!("expected let")
+        }
+    };
+    assert_eq!(lambda(&outer.body[1]),vec!["a"]);
+    assert_eq!(lambda(&outer.body[2]),vec!["b"]);
+    let nested = match &outer.body[2].kind {
+        StatementKind::Let(l) => match &l.exp {
+            Some(Expression { kind:ExpressionKind::Fun(f), .. }) => f,
+            _ => panic!("expected lambda")
+        },
+        _ => panic!("expected let")
+    };
+    assert_eq!(lambda(&nested.body[0]),vec!["b","y"]);
+    assert!(lambda(&stems[2]).is_empty());
+    //a nested fun calling itself is declared inside the fun around it
+    let program = crate::parse_str("fun f() {\n    let n = 1\n    fun rec(i:int) -> int {\n        return rec(i - n)\n    }\n}\n");
+    let rec = match &program.statements[0].kind {
+        StatementKind::Fun(f) => match &f.body[1].kind {
+            StatementKind::Fun(rec) => rec,
+            _ => panic!("expected fun")
+        },
+        _ => panic!("expected fun")
+    };
+    assert_eq!(rec.captures,vec!["n"]);
+}
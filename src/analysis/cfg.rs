@@ -0,0 +1,402 @@
+//control flow graphs of fun bodies. a body is lowered into basic blocks of statements
+//that run one after the other, ifs and fors become conditions that end a block with
+//two edges, the first taken when the condition holds. return, break and continue end
+//their block with an edge to the exit, past the loop or to its next iteration.
+//funs and lambdas nested in a body get graphs of their own, see functions
+use crate::ast::for_::ForStatement;
+use crate::ast::fun::FunExpression;
+use crate::ast::ifs::IfStatement;
+use crate::ast::node::{Expression, ExpressionKind, Statement, StatementKind};
+use crate::ast::visit::{Visitor, walk_expression, walk_program, walk_statement};
+
+pub type BlockId = usize;
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Node<'a> {
+    Stem(&'a Statement),
+    //the condition of an if or a for, always last in its block
+    Cond(&'a Expression),
+}
+#[derive(Debug,Default)]
+pub struct Block<'a> {
+    pub nodes:Vec<Node<'a>>,
+    succs:Vec<BlockId>,
+    preds:Vec<BlockId>,
+}
+#[derive(Debug)]
+pub struct Cfg<'a> {
+    pub blocks:Vec<Block<'a>>,
+}
+//immediate dominator of every block, the entry and unreachable blocks have none
+#[derive(Debug)]
+pub struct Dominators {
+    idom:Vec<Option<BlockId>>,
+}
+
+impl Cfg<'_> {
+    //control starts in ENTRY and every return ends in EXIT, EXIT has no statements
+    pub const ENTRY:BlockId = 0;
+    pub const EXIT:BlockId = 1;
+}
+impl<'a> Cfg<'a> {
+    pub fn build(body:&'a [Statement]) -> Cfg<'a> {
+        let mut b = Builder {
+            blocks:vec![Block::default(),Block::default()],
+            loops:vec![],
+        };
+        if let Some(end) = b.lower(body,Cfg::ENTRY) {
+            b.edge(end,Cfg::EXIT);
+        }
+        Cfg { blocks:b.blocks }
+    }
+    pub fn successors(&self,id:BlockId) -> &[BlockId] {
+        &self.blocks[id].succs
+    }
+    pub fn predecessors(&self,id:BlockId) -> &[BlockId] {
+        &self.blocks[id].preds
+    }
+    //blocks in reverse postorder from the entry, unreachable ones are left out
+    pub fn reverse_postorder(&self) -> Vec<BlockId> {
+        let mut seen = vec![false;self.blocks.len()];
+        let mut order = vec![];
+        //block, index of the next successor to visit
+        let mut stack = vec![(Cfg::ENTRY,0)];
+        seen[Cfg::ENTRY] = true;
+        while let Some((id,i)) = stack.pop() {
+            match self.blocks[id].succs.get(i) {
+                Some(&succ) => {
+                    stack.push((id,i + 1));
+                    if !seen[succ] {
+                        seen[succ] = true;
+                        stack.push((succ,0));
+                    }
+                },
+                None => order.push(id)
+            }
+        }
+        order.reverse();
+        order
+    }
+    pub fn reachable(&self,id:BlockId) -> bool {
+        self.reverse_postorder().contains(&id)
+    }
+    //Cooper, Harvey and Kennedy, "A Simple, Fast Dominance Algorithm"
+    pub fn dominators(&self) -> Dominators {
+        let order = self.reverse_postorder();
+        let mut rank = vec![usize::MAX;self.blocks.len()];
+        for (i,&id) in order.iter().enumerate() {
+            rank[id] = i;
+        }
+        let mut idom:Vec<Option<BlockId>> = vec![None;self.blocks.len()];
+        idom[Cfg::ENTRY] = Some(Cfg::ENTRY);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &id in order.iter().skip(1) {
+                let mut new:Option<BlockId> = None;
+                for &pred in &self.blocks[id].preds {
+                    if idom[pred].is_none() {
+                        continue
+                    }
+                    new = Some(match new {
+                        None => pred,
+                        Some(mut a) => {
+                            let mut b:BlockId = pred;
+                            while a != b {
+                                while rank[a] > rank[b] {
+                                    a = idom[a].unwrap();
+                                }
+                                while rank[b] > rank[a] {
+                                    b = idom[b].unwrap();
+                                }
+                            }
+                            a
+                        }
+                    });
+                }
+                if new.is_some() && idom[id] != new {
+                    idom[id] = new;
+                    changed = true;
+                }
+            }
+        }
+        idom[Cfg::ENTRY] = None;
+        Dominators { idom }
+    }
+    //graphviz source, `dot -Tsvg` draws it. true edges are solid, false edges dashed
+    pub fn dot(&self,name:&str) -> String {
+        let mut out = format!("digraph \"{}\" {{\n    node [shape=box fontname=monospace]\n",escape(name));
+        for (id,block) in self.blocks.iter().enumerate() {
+            let mut label = match id {
+                Cfg::ENTRY => "entry\\l".to_string(),
+                Cfg::EXIT => "exit\\l".to_string(),
+                _ => String::new()
+            };
+            for node in &block.nodes {
+                let text = match node {
+                    //the head of a fun or block statement, its body is elsewhere
+                    Node::Stem(stem) => {
+                        let text = stem.to_string();
+                        match text.split_once('\n') {
+                            Some((head,_)) => format!("{} ...",head),
+                            None => text
+                        }
+                    },
+                    Node::Cond(exp) => format!("{} ?",exp),
+                };
+                label.push_str(&escape(&text));
+                label.push_str("\\l");
+            }
+            out.push_str(&format!("    b{} [label=\"{}\"]\n",id,label));
+        }
+        for (id,block) in self.blocks.iter().enumerate() {
+            let cond = matches!(block.nodes.last(),Some(Node::Cond(_)));
+            for (i,succ) in block.succs.iter().enumerate() {
+                if cond && i == 1 {
+                    out.push_str(&format!("    b{} -> b{} [style=dashed]\n",id,succ));
+                } else {
+                    out.push_str(&format!("    b{} -> b{}\n",id,succ));
+                }
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+impl Dominators {
+    pub fn idom(&self,id:BlockId) -> Option<BlockId> {
+        self.idom[id]
+    }
+    //every path from the entry to b goes through a, a block dominates itself
+    pub fn dominates(&self,a:BlockId,b:BlockId) -> bool {
+        let mut id = Some(b);
+        while let Some(current) = id {
+            if current == a {
+                return true
+            }
+            id = self.idom[current];
+        }
+        false
+    }
+}
+//for ...;true;... { } only ends with a break, see analysis::flow
+pub fn always(condition:&Expression) -> bool {
+    matches!(condition.kind,ExpressionKind::Bool(true))
+}
+fn escape(s:&str) -> String {
+    s.replace('\\',"\\\\").replace('"',"\\\"")
+}
+
+struct Builder<'a> {
+    blocks:Vec<Block<'a>>,
+    //where continue and break go in the loops around, innermost last
+    loops:Vec<(BlockId,BlockId)>,
+}
+impl<'a> Builder<'a> {
+    fn block(&mut self) -> BlockId {
+        self.blocks.push(Block::default());
+        self.blocks.len() - 1
+    }
+    fn edge(&mut self,from:BlockId,to:BlockId) {
+        self.blocks[from].succs.push(to);
+        self.blocks[to].preds.push(from);
+    }
+    fn push(&mut self,id:BlockId,node:Node<'a>) {
+        self.blocks[id].nodes.push(node);
+    }
+    //stems appended to block id, the block control falls out of at the end,
+    //none when every path returns, breaks or continues. statements after that
+    //go to a block with no way in
+    fn lower(&mut self,stems:&'a [Statement],id:BlockId) -> Option<BlockId> {
+        let mut current = Some(id);
+        for stem in stems {
+            let id = match current {
+                Some(id) => id,
+                None => self.block()
+            };
+            current = match &stem.kind {
+                StatementKind::IF(i) => self.lower_if(i,id),
+                StatementKind::For(f) => self.lower_for(f,id),
+                StatementKind::Return(_) => {
+                    self.push(id,Node::Stem(stem));
+                    self.edge(id,Cfg::EXIT);
+                    None
+                },
+                StatementKind::Break | StatementKind::Continue => {
+                    self.push(id,Node::Stem(stem));
+                    //outside of a loop it is an error, see analysis::flow, and leaves the fun
+                    let to = match (self.loops.last(),&stem.kind) {
+                        (Some(&(_,exit)),StatementKind::Break) => exit,
+                        (Some(&(next,_)),_) => next,
+                        (None,_) => Cfg::EXIT
+                    };
+                    self.edge(id,to);
+                    None
+                },
+                StatementKind::Error => Some(id),
+                _ => {
+                    self.push(id,Node::Stem(stem));
+                    Some(id)
+                }
+            };
+        }
+        current
+    }
+    fn lower_if(&mut self,i:&'a IfStatement,id:BlockId) -> Option<BlockId> {
+        self.push(id,Node::Cond(&i.condition));
+        let then = self.block();
+        self.edge(id,then);
+        let mut ends = vec![];
+        ends.extend(self.lower(&i.consequence,then));
+        if i.alternative.is_empty() {
+            ends.push(id);
+        } else {
+            let other = self.block();
+            self.edge(id,other);
+            ends.extend(self.lower(&i.alternative,other));
+        }
+        if ends.is_empty() {
+            return None
+        }
+        let join = self.block();
+        for end in ends {
+            self.edge(end,join);
+        }
+        Some(join)
+    }
+    //init, then a header holding the condition, the body, and a block for the step
+    //that continue jumps to. break and a false condition lead past the loop, a
+    //condition that is always true has no false edge
+    fn lower_for(&mut self,f:&'a ForStatement,id:BlockId) -> Option<BlockId> {
+        self.push(id,Node::Stem(&f.start_condition));
+        let header = self.block();
+        self.edge(id,header);
+        self.push(header,Node::Cond(&f.condition));
+        let body = self.block();
+        let exit = self.block();
+        let step = self.block();
+        self.edge(header,body);
+        if !always(&f.condition) {
+            self.edge(header,exit);
+        }
+        self.loops.push((step,exit));
+        if let Some(end) = self.lower(&f.consequence,body) {
+            self.edge(end,step);
+        }
+        self.loops.pop();
+        self.push(step,Node::Stem(&f.self_operation));
+        self.edge(step,header);
+        if self.blocks[exit].preds.is_empty() {
+            return None
+        }
+        Some(exit)
+    }
+}
+
+//a graph for the top level of the script, named <script>, and one for every fun
+//and lambda in it, named after the fun, or fun@line:col and lambda@line:col
+pub fn functions(stems:&[Statement]) -> Vec<(String,Cfg<'_>)> {
+    let mut c = Collect { funs:vec![] };
+    walk_program(&mut c,stems);
+    let mut graphs = vec![("<script>".to_string(),Cfg::build(stems))];
+    graphs.extend(c.funs.into_iter().map(|(name,f)| (name,Cfg::build(&f.body))));
+    graphs
+}
+//the graphs keep borrows into the tree, Visitor<'a> hands them out for as long as the tree lives
+struct Collect<'a> {
+    funs:Vec<(String,&'a FunExpression)>,
+}
+
+impl<'a> Visitor<'a> for Collect<'a> {
+    fn visit_statement(&mut self,stem:&'a Statement) {
+        if let StatementKind::Fun(f) = &stem.kind {
+            let name = match f.name.as_ref().map(|exp| &exp.kind) {
+                Some(ExpressionKind::Ident(name)) => name.clone(),
+                _ => format!("fun@{}:{}",stem.span.line,stem.span.col)
+            };
+            self.funs.push((name,f));
+        }
+        walk_statement(self,stem);
+    }
+    fn visit_expression(&mut self,exp:&'a Expression) {
+        if let ExpressionKind::Fun(f) = &exp.kind {
+            let kind = if f.lambda { "lambda" } else { "fun" };
+            self.funs.push((format!("{}@{}:{}",kind,exp.span.line,exp.span.col),f));
+        }
+        walk_expression(self,exp);
+    }
+}
+
+#[cfg(test)]
+fn graph(program:&crate::Program,name:&str) -> Vec<(Vec<String>,Vec<BlockId>)> {
+    let graphs = functions(&program.statements);
+    let (_,cfg) = graphs.iter().find(|(n,_)| n == name).unwrap();
+    cfg.blocks.iter().map(|block| {
+        let nodes = block.nodes.iter().map(|node| match node {
+            Node::Stem(stem) => stem.kind.name().to_string(),
+            Node::Cond(exp) => format!("{}?",exp),
+        }).collect();
+        (nodes,block.succs.clone())
+    }).collect()
+}
+#[test]
+fn test_cfg_if(){
+    let program = crate::parse_str("fun f(a:int) -> int {\n    if a > 1 {\n        return 1\n    } else {\n        let b = a\n    }\n    return a\n}\n");
+    let s = |v:&[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+    assert_eq!(graph(&program,"f"),vec![
+        (s(&["a > 1?"]),vec![2,3]),
+        (s(&[]),vec![]),
+        (s(&["Return"]),vec![Cfg::EXIT]),
+        (s(&["Let"]),vec![4]),
+        (s(&["Return"]),vec![Cfg::EXIT]),
+    ]);
+}
+#[test]
+fn test_cfg_for(){
+    let source = "for let i = 0; i < 3; i++ {\n    if i > 1 {\n        break\n    }\n    continue\n    log(i)\n}\nlog(0)\n";
+    let program = crate::parse_str(source);
+    let s = |v:&[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+    //entry, exit, header, body, past the loop, step, then, after the if, unreachable log(i)
+    assert_eq!(graph(&program,"<script>"),vec![
+        (s(&["Let"]),vec![2]),
+        (s(&[]),vec![]),
+        (s(&["i < 3?"]),vec![3,4]),
+        (s(&["i > 1?"]),vec![6,7]),
+        (s(&["Invoke"]),vec![Cfg::EXIT]),
+        (s(&["Invoke"]),vec![2]),
+        (s(&["Break"]),vec![4]),
+        (s(&["Continue"]),vec![5]),
+        (s(&["Invoke"]),vec![5]),
+    ]);
+    let graphs = functions(&program.statements);
+    let cfg = &graphs[0].1;
+    assert_eq!(cfg.predecessors(2),&[0,5]);
+    assert_eq!(cfg.predecessors(4),&[2,6]);
+    assert_eq!(cfg.predecessors(5),&[7,8]);
+    assert!(!cfg.reachable(8));
+}
+#[test]
+fn test_cfg_dominators(){
+    let source = "for let i = 0; i < 3; i++ {\n    if i > 1 {\n        break\n    }\n    continue\n    log(i)\n}\nlog(0)\n";
+    let program = crate::parse_str(source);
+    let graphs = functions(&program.statements);
+    let dom = graphs[0].1.dominators();
+    assert_eq!(dom.idom(Cfg::ENTRY),None);
+    assert_eq!(dom.idom(2),Some(0));
+    assert_eq!(dom.idom(3),Some(2));
+    //past the loop is reached from the header and from the break
+    assert_eq!(dom.idom(4),Some(2));
+    assert_eq!(dom.idom(5),Some(7));
+    assert_eq!(dom.idom(8),None);
+    assert!(dom.dominates(2,6) && dom.dominates(3,3) && !dom.dominates(6,4));
+}
+#[test]
+fn test_cfg_functions_dot(){
+    let program = crate::parse_str("fun f() {\n    let g = |x| x + \"a\"\n}\n");
+    let graphs = functions(&program.statements);
+    let names:Vec<&str> = graphs.iter().map(|(name,_)| name.as_str()).collect();
+    assert_eq!(names,vec!["<script>","f","lambda@2:13"]);
+    assert_eq!(graphs[2].1.dot("lambda"),"digraph \"lambda\" {\n    node [shape=box fontname=monospace]\n\
+        \x20   b0 [label=\"entry\\lreturn x + \\\"a\\\"\\l\"]\n    b1 [label=\"exit\\l\"]\n    b0 -> b1\n}\n");
+    assert!(program.to_dot().starts_with("digraph \"<script>\""));
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use crate::ast::for_::ForStatement;
+use crate::ast::fun::FunExpression;
+use crate::ast::ifs::IfStatement;
+use crate::ast::node::{Expression, ExpressionKind, Statement, StatementKind};
+use crate::ast::visit::{Visitor, walk_expression, walk_program, walk_statement};
+use crate::parser::error::{KzErr, KzError};
+use crate::parser::span::Span;
+
+//const initializers must be built from literals, operators and other consts,
+//and a const can never be assigned to after its declaration
+struct ConstCheck<'a> {
+    path:&'a str,
+    scopes:Vec<HashMap<String,bool>>,
+    errs:Vec<KzError>,
+}
+
+pub fn check(path:&str,stems:&[Statement]) -> Vec<KzError> {
+    let mut c = ConstCheck {
+        path,
+        scopes:vec![],
+        errs:vec![],
+    };
+    c.block(stems);
+    c.errs
+}
+
+impl ConstCheck<'_> {
+    fn block(&mut self,stems:&[Statement]) {
+        self.scopes.push(HashMap::new());
+        walk_program(self,stems);
+        self.scopes.pop();
+    }
+    fn declare(&mut self,name:&str,is_const:bool) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(),is_const);
+        }
+    }
+    fn is_const(&self,name:&str) -> bool {
+        for scope in self.scopes.iter().rev() {
+            if let Some(is_const) = scope.get(name) {
+                return *is_const
+            }
+        }
+        false
+    }
+    fn error(&mut self,span:Span,err:KzErr) {
+        self.errs.push(KzError::at(self.path,span,err));
+    }
+    //a = 1  a[0] = 1  a.b = 1  a++
+    fn assign(&mut self,target:&Expression) {
+        match &target.kind {
+            ExpressionKind::Ident(name) if self.is_const(name) => {
+                self.error(target.span,KzErr::ConstAssign(name.clone()));
+            },
+            ExpressionKind::Index(left,_) | ExpressionKind::Field(left,_) => self.assign(left),
+            _ => {}
+        }
+    }
+    fn const_expression(&self,exp:&Expression) -> bool {
+        match &exp.kind {
+            ExpressionKind::Int(_) | ExpressionKind::Float(_) | ExpressionKind::String(_)
+            | ExpressionKind::Bool(_) | ExpressionKind::Nil => true,
+            ExpressionKind::Infix(infix) => self.const_expression(&infix.left) && self.const_expression(&infix.right),
+            ExpressionKind::ListValue(list) | ExpressionKind::Tuple(list) => list.iter().all(|exp| self.const_expression(exp)),
+            ExpressionKind::Ident(name) => self.is_const(name),
+            _ => false
+        }
+    }
+}
+
+impl Visitor<'_> for ConstCheck<'_> {
+    fn visit_statement(&mut self,stem:&Statement) {
+        match &stem.kind {
+            StatementKind::Let(l) => {
+                walk_statement(self,stem);
+                for name in l.names() {
+                    self.declare(name,false);
+                }
+            },
+            StatementKind::Const(c) => {
+                if let Some(exp) = &c.exp {
+                    if !self.const_expression(exp) {
+                        self.error(stem.span,KzErr::ConstExpr(c.ident.clone()));
+                    }
+                }
+                walk_statement(self,stem);
+                self.declare(&c.ident,true);
+            },
+            StatementKind::Assign(target,_) => {
+                self.assign(target);
+                walk_statement(self,stem);
+            },
+            StatementKind::Type(t) => self.declare(&t.name,false),
+            StatementKind::Defer(stems) => self.block(stems),
+            _ => walk_statement(self,stem)
+        }
+    }
+    fn visit_expression(&mut self,exp:&Expression) {
+        if let ExpressionKind::SelfOp(target,_,_) = &exp.kind {
+            self.assign(target);
+        }
+        walk_expression(self,exp);
+    }
+    fn visit_if(&mut self,i:&IfStatement) {
+        self.visit_expression(&i.condition);
+        self.scopes.push(HashMap::new());
+        if let Some(name) = &i.binding {
+            self.declare(name,false);
+        }
+        self.block(&i.consequence);
+        self.scopes.pop();
+        self.block(&i.alternative);
+    }
+    fn visit_for(&mut self,f:&ForStatement) {
+        self.scopes.push(HashMap::new());
+        self.visit_statement(&f.start_condition);
+        self.visit_expression(&f.condition);
+        self.visit_statement(&f.self_operation);
+        self.block(&f.consequence);
+        self.scopes.pop();
+    }
+    fn visit_fun(&mut self,f:&FunExpression) {
+        if let Some(Expression { kind:ExpressionKind::Ident(name), .. }) = &f.name {
+            self.declare(name,false);
+        }
+        self.scopes.push(HashMap::new());
+        for exp in f.param_exp.iter().flatten() {
+            self.visit_expression(exp);
+            if let ExpressionKind::Param(param) = &exp.kind {
+                self.declare(&param.name,false);
+            }
+        }
+        self.block(&f.body);
+        self.scopes.pop();
+    }
+}
+
+#[test]
+fn test_const_check() {
+    let path = "./src/script/13_parser.kz";
+    assert_eq!(super::lines(check(path,&super::fixture(path))),vec![
+        (5,KzErr::ConstExpr("BAD".to_string())),
+        (6,KzErr::ConstAssign("MAX".to_string())),
+        (9,KzErr::ConstAssign("LIMIT".to_string())),
+    ]);
+}
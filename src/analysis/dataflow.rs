@@ -0,0 +1,324 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::analysis::cfg::{Cfg, Node};
+use crate::analysis::resolve::{SymbolKind, SymbolTable};
+use crate::ast::node::{Expression, ExpressionKind, Statement, StatementKind};
+use crate::ast::visit::{Visitor, walk_expression, walk_statement};
+use crate::parser::error::{KzErr, KzError};
+use crate::parser::span::Span;
+
+//what a node of a graph does to a variable, in the order it happens
+#[derive(Debug,Clone,Copy)]
+enum Access {
+    //let x, no value yet
+    Declare(usize),
+    Write(usize,Span),
+    Read(usize,Span),
+}
+
+struct Dataflow<'a> {
+    path:&'a str,
+    symbols:&'a SymbolTable,
+    declared:HashMap<(Span,String),usize>,
+    //reads of every symbol in the whole program
+    reads:HashMap<usize,usize>,
+    errs:Vec<KzError>,
+}
+
+//warnings over the control flow graph of every fun, see analysis::cfg: a let read on a path
+//where it was never assigned, a let or parameter that is never read, and a value stored in a
+//let that no path reads before it is overwritten or the fun ends. a let read by another fun,
+//a lambda or a defer may be read at any time and is only checked for being unused.
+//names starting with _ are never unused
+pub fn check(path:&str,stems:&[Statement],symbols:&SymbolTable) -> Vec<KzError> {
+    let mut reads = Reads {
+        symbols,
+        reads:HashMap::new(),
+        exported:HashSet::new(),
+    };
+    crate::ast::visit::walk_program(&mut reads,stems);
+    let mut d = Dataflow {
+        path,
+        symbols,
+        declared:symbols.symbols.iter().enumerate().map(|(id,symbol)| ((symbol.span,symbol.name.clone()),id)).collect(),
+        reads:reads.reads,
+        errs:vec![],
+    };
+    for (id,symbol) in symbols.symbols.iter().enumerate() {
+        if symbol.name.starts_with('_') || d.reads.contains_key(&id) {
+            continue
+        }
+        match symbol.kind {
+            SymbolKind::Let if !reads.exported.contains(&symbol.span) => {
+                d.error(symbol.span,KzErr::UnusedVariable(symbol.name.clone()));
+            },
+            SymbolKind::Param => d.error(symbol.span,KzErr::UnusedParam(symbol.name.clone())),
+            _ => {}
+        }
+    }
+    for (_,cfg) in crate::analysis::cfg::functions(stems) {
+        d.graph(&cfg);
+    }
+    d.errs.sort_by_key(|e| e.span().start);
+    d.errs
+}
+
+impl Dataflow<'_> {
+    fn error(&mut self,span:Span,err:KzErr) {
+        self.errs.push(KzError::at(self.path,span,err));
+    }
+    fn graph(&mut self,cfg:&Cfg) {
+        let accesses:Vec<Vec<Access>> = cfg.blocks.iter().map(|block| {
+            let mut a = Accesses { d:self, out:vec![] };
+            for node in &block.nodes {
+                match node {
+                    Node::Stem(stem) => a.statement(stem),
+                    Node::Cond(exp) => a.expression(exp),
+                }
+            }
+            a.out
+        }).collect();
+        //lets of this graph that no other graph reads
+        let mut local:HashMap<usize,usize> = HashMap::new();
+        let mut lets = HashSet::new();
+        for access in accesses.iter().flatten() {
+            match access {
+                Access::Read(id,_) => *local.entry(*id).or_default() += 1,
+                Access::Declare(id) | Access::Write(id,_) if self.symbols.symbols[*id].kind == SymbolKind::Let => {
+                    lets.insert(*id);
+                },
+                _ => {}
+            }
+        }
+        let checked:HashSet<usize> = lets.into_iter()
+            .filter(|id| local.get(id).copied().unwrap_or(0) == self.reads.get(id).copied().unwrap_or(0))
+            .collect();
+        let order = cfg.reverse_postorder();
+        self.unassigned(cfg,&order,&accesses,&checked);
+        self.dead_stores(cfg,&order,&accesses,&checked);
+    }
+    //forward, a let is assigned at a block when it is on every path into it
+    fn unassigned(&mut self,cfg:&Cfg,order:&[usize],accesses:&[Vec<Access>],checked:&HashSet<usize>) {
+        let transfer = |set:&mut HashSet<usize>,access:&Access| match access {
+            Access::Declare(id) => {
+                set.remove(id);
+            },
+            Access::Write(id,_) => {
+                set.insert(*id);
+            },
+            Access::Read(_,_) => {}
+        };
+        //none until a block is first reached, that is every let
+        let mut outs:Vec<Option<HashSet<usize>>> = vec![None;cfg.blocks.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &id in order {
+                let mut set = self.assigned_in(cfg,&outs,id);
+                for access in &accesses[id] {
+                    transfer(&mut set,access);
+                }
+                if outs[id].as_ref() != Some(&set) {
+                    outs[id] = Some(set);
+                    changed = true;
+                }
+            }
+        }
+        let mut reported = HashSet::new();
+        for &id in order {
+            let mut set = self.assigned_in(cfg,&outs,id);
+            for access in &accesses[id] {
+                if let Access::Read(symbol,span) = access {
+                    if checked.contains(symbol) && !set.contains(symbol) && reported.insert(*symbol) {
+                        self.error(*span,KzErr::ReadBeforeAssign(self.symbols.symbols[*symbol].name.clone()));
+                    }
+                }
+                transfer(&mut set,access);
+            }
+        }
+    }
+    fn assigned_in(&self,cfg:&Cfg,outs:&[Option<HashSet<usize>>],id:usize) -> HashSet<usize> {
+        let mut preds = cfg.predecessors(id).iter().filter_map(|pred| outs[*pred].as_ref());
+        match preds.next() {
+            Some(first) => preds.fold(first.clone(),|set,other| set.intersection(other).copied().collect()),
+            None => HashSet::new()
+        }
+    }
+    //backward, a let is live after a node when some path from there reads it before writing it
+    fn dead_stores(&mut self,cfg:&Cfg,order:&[usize],accesses:&[Vec<Access>],checked:&HashSet<usize>) {
+        let transfer = |set:&mut HashSet<usize>,access:&Access| match access {
+            Access::Declare(id) | Access::Write(id,_) => {
+                set.remove(id);
+            },
+            Access::Read(id,_) => {
+                set.insert(*id);
+            }
+        };
+        let live_out = |ins:&[HashSet<usize>],id:usize| -> HashSet<usize> {
+            cfg.successors(id).iter().flat_map(|succ| ins[*succ].iter().copied()).collect()
+        };
+        let mut ins:Vec<HashSet<usize>> = vec![HashSet::new();cfg.blocks.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &id in order.iter().rev() {
+                let mut set = live_out(&ins,id);
+                for access in accesses[id].iter().rev() {
+                    transfer(&mut set,access);
+                }
+                if ins[id] != set {
+                    ins[id] = set;
+                    changed = true;
+                }
+            }
+        }
+        for &id in order {
+            let mut set = live_out(&ins,id);
+            for access in accesses[id].iter().rev() {
+                if let Access::Write(symbol,span) = access {
+                    //one that is never read at all is an unused variable
+                    if checked.contains(symbol) && self.reads.contains_key(symbol) && !set.contains(symbol) {
+                        self.error(*span,KzErr::DeadStore(self.symbols.symbols[*symbol].name.clone()));
+                    }
+                }
+                transfer(&mut set,access);
+            }
+        }
+    }
+}
+
+//the accesses of the nodes of one graph, funs and lambdas inside are graphs of their own
+//and a defer runs when its scope exits, neither is looked into
+struct Accesses<'a,'b> {
+    d:&'a Dataflow<'b>,
+    out:Vec<Access>,
+}
+impl Accesses<'_,'_> {
+    fn write(&mut self,exp:&Expression,span:Span) {
+        if let Some(id) = self.d.symbols.binding_id(exp.span) {
+            self.out.push(Access::Write(id,span));
+        }
+    }
+    fn statement(&mut self,stem:&Statement) {
+        match &stem.kind {
+            StatementKind::Let(l) | StatementKind::Const(l) => {
+                if let Some(exp) = &l.exp {
+                    self.expression(exp);
+                }
+                for name in l.names() {
+                    if let Some(id) = self.d.declared.get(&(stem.span,name.to_string())) {
+                        self.out.push(if l.exp.is_some() { Access::Write(*id,stem.span) } else { Access::Declare(*id) });
+                    }
+                }
+            },
+            StatementKind::Assign(target,exp) => {
+                self.expression(exp);
+                match &target.kind {
+                    ExpressionKind::Ident(_) => self.write(target,stem.span),
+                    _ => self.expression(target)
+                }
+            },
+            StatementKind::Invoke(exp) | StatementKind::Return(Some(exp)) => self.expression(exp),
+            StatementKind::Pub(stem) => self.statement(stem),
+            _ => {}
+        }
+    }
+    fn expression(&mut self,exp:&Expression) {
+        match &exp.kind {
+            ExpressionKind::Ident(_) => {
+                if let Some(id) = self.d.symbols.binding_id(exp.span) {
+                    self.out.push(Access::Read(id,exp.span));
+                }
+            },
+            ExpressionKind::Infix(infix) => {
+                self.expression(&infix.left);
+                self.expression(&infix.right);
+            },
+            ExpressionKind::ListValue(list) | ExpressionKind::Tuple(list) => list.iter().for_each(|exp| self.expression(exp)),
+            ExpressionKind::Call(f,args) => {
+                self.expression(f);
+                args.iter().for_each(|exp| self.expression(exp));
+            },
+            ExpressionKind::Index(left,index) => {
+                self.expression(left);
+                self.expression(index);
+            },
+            //i++ reads i and stores the result
+            ExpressionKind::SelfOp(target,_,_) => {
+                self.expression(target);
+                if let ExpressionKind::Ident(_) = target.kind {
+                    self.write(target,exp.span);
+                }
+            },
+            ExpressionKind::Field(exp,_) | ExpressionKind::NamedArg(_,exp) | ExpressionKind::Try(exp) => self.expression(exp),
+            _ => {}
+        }
+    }
+}
+
+//every read in the program, an assigned name is not read, a pub let is read by importers
+struct Reads<'a> {
+    symbols:&'a SymbolTable,
+    reads:HashMap<usize,usize>,
+    exported:HashSet<Span>,
+}
+impl Visitor<'_> for Reads<'_> {
+    fn visit_statement(&mut self,stem:&Statement) {
+        match &stem.kind {
+            StatementKind::Assign(target,exp) if matches!(target.kind,ExpressionKind::Ident(_)) => self.visit_expression(exp),
+            StatementKind::Pub(inner) => {
+                self.exported.insert(inner.span);
+                walk_statement(self,stem);
+            },
+            _ => walk_statement(self,stem)
+        }
+    }
+    fn visit_expression(&mut self,exp:&Expression) {
+        if let ExpressionKind::Ident(_) = exp.kind {
+            if let Some(id) = self.symbols.binding_id(exp.span) {
+                *self.reads.entry(id).or_default() += 1;
+            }
+        }
+        walk_expression(self,exp);
+    }
+}
+
+#[test]
+fn test_dataflow_unassigned(){
+    let source = "fun f(c:bool) -> int {\n    let x:int\n    let y:int\n    if c {\n        x = 1\n        y = 1\n    } else {\n        y = 2\n    }\n    return x + y\n}\n\
+        fun g() -> int {\n    let z:int\n    for let i = 0; i < 3; i++ {\n        z = i\n    }\n    return z\n}\n\
+        fun h() -> int {\n    let w:int\n    for let i = 0; true; i++ {\n        w = i\n        break\n    }\n    return w\n}\n";
+    //a for that is always true only leaves through the break, after w is assigned
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements,&p.symbols)),vec![
+        (10,KzErr::ReadBeforeAssign("x".to_string())),
+        (17,KzErr::ReadBeforeAssign("z".to_string())),
+    ]);
+}
+#[test]
+fn test_dataflow_unused(){
+    //a pub let is read by importers, _ opts out, a read by a lambda counts
+    let source = "let a = 1\npub let b = 2\nlet _c = 3\nlet d = 4\nlet f = |x, _y| d\nfun g(p:int, q:int) -> int {\n    return q + f(1, 2)\n}\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements,&p.symbols)),vec![
+        (1,KzErr::UnusedVariable("a".to_string())),
+        (5,KzErr::UnusedParam("x".to_string())),
+        (6,KzErr::UnusedParam("p".to_string())),
+    ]);
+}
+#[test]
+fn test_dataflow_dead_store(){
+    let source = "fun f(c:bool) -> int {\n    let a = 0\n    a = 1\n    let b = 0\n    if c {\n        b = 1\n    }\n    a = a + b\n    a = 2\n    return a\n}\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements,&p.symbols)),vec![
+        (2,KzErr::DeadStore("a".to_string())),
+        (8,KzErr::DeadStore("a".to_string())),
+    ]);
+    //the defer may read it after any of the writes
+    let source = "fun g() {\n    let f = open()\n    defer close(f)\n    f = open()\n}\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements,&p.symbols)),vec![]);
+}
+#[test]
+fn test_dataflow_severity(){
+    let program = crate::parse_str("let a = 1\n");
+    assert_eq!(program.diagnostics[0].severity(),crate::parser::error::Severity::Warning);
+    assert!(program.is_ok());
+    assert!(program.diagnostics[0].to_string().ends_with("warning: 'a' is never read"));
+}
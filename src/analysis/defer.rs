@@ -0,0 +1,48 @@
+use crate::ast::fun::FunExpression;
+use crate::ast::node::{Statement, StatementKind};
+use crate::ast::visit::{Visitor, walk_fun, walk_program, walk_statement};
+use crate::parser::error::{KzErr, KzError};
+
+struct DeferCheck<'a> {
+    path:&'a str,
+    //funs around the statement being walked
+    funs:usize,
+    errs:Vec<KzError>,
+}
+
+//a deferred action runs when the fun around it returns, outside of any fun there is
+//nothing to run it, not even in an if or for block. inside a fun body any block will do
+pub fn check(path:&str,stems:&[Statement]) -> Vec<KzError> {
+    let mut d = DeferCheck {
+        path,
+        funs:0,
+        errs:vec![],
+    };
+    walk_program(&mut d,stems);
+    d.errs
+}
+
+impl Visitor<'_> for DeferCheck<'_> {
+    fn visit_statement(&mut self,stem:&Statement) {
+        match &stem.kind {
+            //the defers nested in it are part of the one reported
+            StatementKind::Defer(_) if self.funs == 0 => {
+                self.errs.push(KzError::at(self.path,stem.span,KzErr::Defer));
+            },
+            _ => walk_statement(self,stem)
+        }
+    }
+    fn visit_fun(&mut self,f:&FunExpression) {
+        self.funs += 1;
+        walk_fun(self,f);
+        self.funs -= 1;
+    }
+}
+
+#[test]
+fn test_defer_check() {
+    let path = "./src/script/18_parser.kz";
+    assert_eq!(super::lines(check(path,&super::fixture(path))),vec![(13,KzErr::Defer)]);
+    let source = "if true {\n    defer close()\n}\nfor let i = 0; i < 3; i++ {\n    defer {\n        defer close()\n    }\n}\nlet f = || {\n    if true {\n        defer close()\n    }\n}\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements)),vec![(2,KzErr::Defer),(5,KzErr::Defer)]);
+}
@@ -0,0 +1,166 @@
+use crate::analysis::cfg::always;
+use crate::ast::for_::ForStatement;
+use crate::ast::fun::FunExpression;
+use crate::ast::ifs::IfStatement;
+use crate::ast::node::{Expression, ExpressionKind, Statement, StatementKind};
+use crate::ast::visit::{Visitor, walk_expression, walk_statement};
+use crate::parser::error::{KzErr, KzError};
+use crate::parser::span::Span;
+use crate::parser::token::Token;
+
+struct Flow<'a> {
+    path:&'a str,
+    //loops around the statement being walked, a fun starts again from none
+    loops:usize,
+    errs:Vec<KzError>,
+}
+
+//funs declared to return a value that can reach the end of their body, statements that
+//follow a return, break or continue, and break/continue outside of a for
+pub fn check(path:&str,stems:&[Statement]) -> Vec<KzError> {
+    let mut flow = Flow {
+        path,
+        loops:0,
+        errs:vec![],
+    };
+    flow.block(stems);
+    flow.errs
+}
+
+//control never gets past stem, every path through it returns, breaks or continues.
+//a break or continue outside of a loop is reported on its own and does not exit
+fn exits(stem:&Statement,looped:bool) -> bool {
+    match &stem.kind {
+        StatementKind::Return(_) => true,
+        StatementKind::Break | StatementKind::Continue => looped,
+        StatementKind::IF(i) => !i.alternative.is_empty() && block_exits(&i.consequence,looped) && block_exits(&i.alternative,looped),
+        //for ...;true;... { } only ends with a break, a return leaves the fun
+        StatementKind::For(f) => always(&f.condition) && !breaks(&f.consequence),
+        _ => false
+    }
+}
+fn block_exits(stems:&[Statement],looped:bool) -> bool {
+    stems.iter().any(|stem| exits(stem,looped))
+}
+//a break out of the loop whose body is stems, the ones of nested loops do not count
+fn breaks(stems:&[Statement]) -> bool {
+    stems.iter().any(|stem| match &stem.kind {
+        StatementKind::Break => true,
+        StatementKind::IF(i) => breaks(&i.consequence) || breaks(&i.alternative),
+        StatementKind::Defer(stems) => breaks(stems),
+        _ => false
+    })
+}
+
+impl Flow<'_> {
+    fn error(&mut self,stem:&Statement,err:KzErr) {
+        self.errs.push(KzError::at(self.path,stem.span,err));
+    }
+    //the first statement after one that exits is reported, the rest of the block with it
+    fn block(&mut self,stems:&[Statement]) {
+        let mut exited = false;
+        for stem in stems {
+            if exited && !matches!(stem.kind,StatementKind::Error) {
+                self.error(stem,KzErr::Unreachable);
+                exited = false;
+            }
+            self.visit_statement(stem);
+            if exits(stem,self.loops > 0) {
+                exited = true;
+            }
+        }
+    }
+}
+
+impl Visitor<'_> for Flow<'_> {
+    fn visit_statement(&mut self,stem:&Statement) {
+        match &stem.kind {
+            StatementKind::Break if self.loops == 0 => self.error(stem,KzErr::OutsideLoop(Token::Break)),
+            StatementKind::Continue if self.loops == 0 => self.error(stem,KzErr::OutsideLoop(Token::Continue)),
+            StatementKind::Defer(stems) => self.block(stems),
+            StatementKind::Fun(f) => self.fun(f,stem.span),
+            _ => walk_statement(self,stem)
+        }
+    }
+    fn visit_if(&mut self,i:&IfStatement) {
+        self.visit_expression(&i.condition);
+        self.block(&i.consequence);
+        self.block(&i.alternative);
+    }
+    fn visit_for(&mut self,f:&ForStatement) {
+        self.visit_statement(&f.start_condition);
+        self.visit_expression(&f.condition);
+        self.visit_statement(&f.self_operation);
+        self.loops += 1;
+        self.block(&f.consequence);
+        self.loops -= 1;
+    }
+    fn visit_expression(&mut self,exp:&Expression) {
+        match &exp.kind {
+            ExpressionKind::Fun(f) => self.fun(f,exp.span),
+            _ => walk_expression(self,exp)
+        }
+    }
+}
+
+impl Flow<'_> {
+    //span is the fun statement or expression, an unnamed fun is labelled as in analysis::cfg
+    fn fun(&mut self,f:&FunExpression,span:Span) {
+        //a lambda returns whatever its body gives, only declared return types are checked
+        if let (false,Some(ret)) = (f.lambda,&f.ret_type) {
+            if *ret != Token::Unknown && !block_exits(&f.body,false) {
+                let (name,span) = match f.name.as_ref() {
+                    Some(Expression { kind:ExpressionKind::Ident(ident), span }) => (ident.clone(),*span),
+                    _ => (format!("fun@{}:{}",span.line,span.col),span)
+                };
+                self.errs.push(KzError::at(self.path,span,KzErr::MissingReturn(name,ret.clone())));
+            }
+        }
+        let loops = self.loops;
+        self.loops = 0;
+        for exp in f.param_exp.iter().flatten() {
+            self.visit_expression(exp);
+        }
+        self.block(&f.body);
+        self.loops = loops;
+    }
+}
+
+#[test]
+fn test_flow_missing_return(){
+    let source = "fun a(x:int) -> int {\n    if x > 0 {\n        return 1\n    } else {\n        return 2\n    }\n}\n\
+        fun b(x:int) -> int {\n    if x > 0 {\n        return 1\n    }\n}\n\
+        fun c() -> int {\n    for let i = 0; true; i++ {\n    }\n}\n\
+        fun d() -> int {\n    for let i = 0; true; i++ {\n        break\n    }\n}\n\
+        let e = |x| {\n    let y = x\n}\n\
+        let f = fun (x:int) -> int {\n    let y = x\n}\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements)),vec![
+        (8,KzErr::MissingReturn("b".to_string(),Token::Int)),
+        (17,KzErr::MissingReturn("d".to_string(),Token::Int)),
+        (25,KzErr::MissingReturn("fun@25:9".to_string(),Token::Int)),
+    ]);
+}
+#[test]
+fn test_flow_unreachable(){
+    let source = "fun a(x:int) -> int {\n    if x > 0 {\n        return 1\n    } else {\n        return 2\n    }\n    let y = 1\n    let z = 2\n}\n\
+        for let i = 0; i < 3; i++ {\n    continue\n    i++\n}\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements)),vec![(7,KzErr::Unreachable),(12,KzErr::Unreachable)]);
+    //a bare return leaves a void fun early
+    let source = "fun f(x:int) {\n    if x > 0 {\n        return\n    }\n    return\n    let y = x\n}\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements)),vec![(6,KzErr::Unreachable)]);
+}
+#[test]
+fn test_flow_outside_loop(){
+    //a lambda body is not inside the loop it is written in
+    let source = "break\nfor let i = 0; i < 3; i++ {\n    if i > 1 {\n        break\n    }\n    let f = || {\n        continue\n    }\n}\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements)),vec![
+        (1,KzErr::OutsideLoop(Token::Break)),
+        (7,KzErr::OutsideLoop(Token::Continue)),
+    ]);
+    //stray breaks in both branches do not make what follows unreachable
+    let source = "let a = 1\nif a > 0 {\n    break\n} else {\n    continue\n}\nlet b = a\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements)),vec![
+        (3,KzErr::OutsideLoop(Token::Break)),
+        (5,KzErr::OutsideLoop(Token::Continue)),
+    ]);
+}
@@ -0,0 +1,43 @@
+use crate::ast::node::Statement;
+use crate::parser::error::KzError;
+
+pub mod capture;
+pub mod cfg;
+pub mod consts;
+pub mod dataflow;
+pub mod defer;
+pub mod flow;
+pub mod params;
+pub mod propagate;
+pub mod resolve;
+pub mod types;
+
+//run every check over a parsed program
+pub fn check(path:&str,stems:&[Statement]) -> Vec<KzError> {
+    let mut errs = Vec::new();
+    errs.extend(consts::check(path,stems));
+    errs.extend(params::check(path,stems));
+    errs.extend(propagate::check(path,stems));
+    errs.extend(defer::check(path,stems));
+    errs.extend(flow::check(path,stems));
+    errs
+}
+
+#[cfg(test)]
+//a fixture as the parser gives it, none of the checks have run
+pub(crate) fn fixture(path:&str) -> Vec<Statement> {
+    use crate::parser::file::PaserFile;
+    use crate::parser::lexer::Lexer;
+    use crate::parser::parse::Parser;
+    Parser::new(Lexer::new(PaserFile::new(path))).program().unwrap()
+}
+#[cfg(test)]
+//line and kind of every diagnostic, in the order reported
+pub(crate) fn lines(errs:Vec<KzError>) -> Vec<(i32,crate::parser::error::KzErr)> {
+    errs.into_iter().map(|e| (e.span().line,e.err().clone())).collect()
+}
+#[cfg(test)]
+//what one check reports on source, given the program parse_str makes of it
+pub(crate) fn check_str(source:&str,check:impl Fn(&crate::Program) -> Vec<KzError>) -> Vec<(i32,crate::parser::error::KzErr)> {
+    lines(check(&crate::parse_str(source)))
+}
@@ -0,0 +1,64 @@
+use crate::ast::fun::FunExpression;
+use crate::ast::node::{Expression, ExpressionKind, Statement};
+use crate::ast::visit::{Visitor, walk_expression, walk_fun, walk_program};
+use crate::parser::error::{KzErr, KzError};
+
+struct ParamCheck<'a> {
+    path:&'a str,
+    errs:Vec<KzError>,
+}
+
+//parameters with a default value must come last,
+//and a single variadic parameter may only be the very last one
+pub fn check(path:&str,stems:&[Statement]) -> Vec<KzError> {
+    let mut c = ParamCheck {
+        path,
+        errs:vec![],
+    };
+    walk_program(&mut c,stems);
+    c.errs
+}
+
+impl ParamCheck<'_> {
+    fn param_list(&mut self,params:&[Expression]) {
+        let mut default = false;
+        for (i,exp) in params.iter().enumerate() {
+            if let ExpressionKind::Param(param) = &exp.kind {
+                if param.variadic {
+                    if i != params.len() - 1 {
+                        self.errs.push(KzError::at(self.path,exp.span,KzErr::ParamVariadic(param.name.clone())));
+                    }
+                } else if param.default.is_some() {
+                    default = true;
+                } else if default {
+                    self.errs.push(KzError::at(self.path,exp.span,KzErr::ParamDefault(param.name.clone())));
+                }
+            }
+        }
+    }
+}
+
+impl Visitor<'_> for ParamCheck<'_> {
+    fn visit_fun(&mut self,f:&FunExpression) {
+        if let Some(params) = &f.param_exp {
+            self.param_list(params);
+        }
+        walk_fun(self,f);
+    }
+    fn visit_expression(&mut self,exp:&Expression) {
+        if let ExpressionKind::FunType(Some(params),_) = &exp.kind {
+            self.param_list(params);
+        }
+        walk_expression(self,exp);
+    }
+}
+
+#[test]
+fn test_param_check() {
+    let path = "./src/script/15_parser.kz";
+    assert_eq!(super::lines(check(path,&super::fixture(path))),vec![
+        (6,KzErr::ParamDefault("b".to_string())),
+        (8,KzErr::ParamVariadic("rest".to_string())),
+        (10,KzErr::ParamVariadic("a".to_string())),
+    ]);
+}
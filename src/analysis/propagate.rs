@@ -0,0 +1,68 @@
+use crate::ast::fun::FunExpression;
+use crate::ast::node::{Expression, ExpressionKind, Statement};
+use crate::ast::visit::{Visitor, walk_expression, walk_fun, walk_program};
+use crate::parser::error::{KzErr, KzError};
+use crate::parser::token::Token;
+
+//`f()?` hands the error to the caller, so it is only valid inside
+//a function whose return type is `error` or a tuple ending in `error`
+struct Propagate<'a> {
+    path:&'a str,
+    //return types of the functions being walked, innermost last. a fun without
+    //one returns nothing, none for a lambda whose return type is left to inference
+    rets:Vec<Option<Token>>,
+    errs:Vec<KzError>,
+}
+
+pub fn check(path:&str,stems:&[Statement]) -> Vec<KzError> {
+    let mut p = Propagate {
+        path,
+        rets:vec![],
+        errs:vec![],
+    };
+    walk_program(&mut p,stems);
+    p.errs
+}
+
+//int -> false  error -> true  (int,error) -> true
+pub fn carries_error(tok:&Token) -> bool {
+    match tok {
+        Token::Ident(name) => name == "error",
+        Token::Tuple(toks) => toks.last().is_some_and(carries_error),
+        _ => false
+    }
+}
+
+impl Visitor<'_> for Propagate<'_> {
+    fn visit_fun(&mut self,f:&FunExpression) {
+        let ret = match &f.ret_type {
+            //a lambda returns whatever its body gives, that is not known here, see analysis::types
+            Some(Token::Unknown) | None if f.lambda => None,
+            ret => Some(ret.clone().unwrap_or(Token::Unknown))
+        };
+        self.rets.push(ret);
+        walk_fun(self,f);
+        self.rets.pop();
+    }
+    fn visit_expression(&mut self,exp:&Expression) {
+        if let ExpressionKind::Try(_) = &exp.kind {
+            match self.rets.last() {
+                None => self.errs.push(KzError::at(self.path,exp.span,KzErr::Try(None))),
+                Some(Some(ret)) if !carries_error(ret) => {
+                    self.errs.push(KzError::at(self.path,exp.span,KzErr::Try(Some(ret.clone()))));
+                },
+                Some(_) => {}
+            }
+        }
+        walk_expression(self,exp);
+    }
+}
+
+#[test]
+fn test_propagate_check() {
+    let path = "./src/script/17_parser.kz";
+    assert_eq!(super::lines(check(path,&super::fixture(path))),vec![
+        (10,KzErr::Try(Some(Token::Int))),
+        (14,KzErr::Try(None)),
+    ]);
+}
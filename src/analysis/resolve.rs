@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::ast::fun::FunExpression;
+use crate::ast::node::{Expression, ExpressionKind, Statement, StatementKind};
+use crate::ast::visit::{Visitor, walk_expression, walk_statement};
+use crate::parser::error::{KzErr, KzError};
+use crate::parser::span::Span;
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum ScopeKind {
+    Global,
+    //parameters and the body of a fun or lambda
+    Function,
+    //if, else and defer bodies, the body of a for
+    Block,
+    //the `let i` of a for, around its body
+    ForInit,
+    //the binding of an if let, around the body it holds in
+    IfLet,
+}
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum SymbolKind {
+    Let,
+    Const,
+    Fun,
+    Param,
+    Type,
+    Import,
+    //if let x = maybe
+    Binding,
+}
+
+#[derive(Debug)]
+pub struct Scope {
+    pub kind:ScopeKind,
+    pub parent:Option<usize>,
+    //indexes into SymbolTable::symbols in declaration order
+    pub symbols:Vec<usize>,
+}
+
+#[derive(Debug)]
+pub struct Symbol {
+    pub name:String,
+    pub kind:SymbolKind,
+    //the declaring statement, parameter or fun name
+    pub span:Span,
+    pub scope:usize,
+}
+
+//every scope and declaration of a program, and the declaration each Ident expression refers to.
+//scope 0 is the global scope, idents are keyed by their span
+#[derive(Debug,Default)]
+pub struct SymbolTable {
+    pub scopes:Vec<Scope>,
+    pub symbols:Vec<Symbol>,
+    bindings:HashMap<Span,usize>,
+}
+
+impl SymbolTable {
+    //the declaration of the Ident expression at span
+    pub fn binding(&self,span:Span) -> Option<&Symbol> {
+        self.bindings.get(&span).map(|id| &self.symbols[*id])
+    }
+    pub fn binding_id(&self,span:Span) -> Option<usize> {
+        self.bindings.get(&span).copied()
+    }
+    //spans of every Ident bound to symbols[id], in source order
+    pub fn references(&self,id:usize) -> Vec<Span> {
+        let mut spans:Vec<Span> = self.bindings.iter().filter(|(_,s)| **s == id).map(|(span,_)| *span).collect();
+        spans.sort_by_key(|span| span.start);
+        spans
+    }
+    //name as seen from scope, the latest declaration in the nearest scope wins
+    pub fn lookup(&self,scope:usize,name:&str) -> Option<&Symbol> {
+        self.lookup_id(scope,name).map(|id| &self.symbols[id])
+    }
+    fn lookup_id(&self,scope:usize,name:&str) -> Option<usize> {
+        let mut scope = Some(scope);
+        while let Some(index) = scope {
+            let found = self.scopes[index].symbols.iter().rev().find(|id| self.symbols[**id].name == name);
+            if let Some(id) = found {
+                return Some(*id)
+            }
+            scope = self.scopes[index].parent;
+        }
+        None
+    }
+}
+
+struct Resolver<'a> {
+    path:&'a str,
+    table:SymbolTable,
+    //open scopes, innermost last, with the let and const names declared further down each of them
+    stack:Vec<(usize,Vec<String>)>,
+    errs:Vec<KzError>,
+}
+
+//binds identifiers to declarations. fun and type declarations are visible in their whole block,
+//let and const only below themselves; undefined names, a name declared twice in one scope and
+//a let used above its declaration are reported
+pub fn resolve(path:&str,stems:&[Statement]) -> (SymbolTable,Vec<KzError>) {
+    let mut r = Resolver {
+        path,
+        table:SymbolTable::default(),
+        stack:vec![],
+        errs:vec![],
+    };
+    r.block(ScopeKind::Global,stems);
+    (r.table,r.errs)
+}
+
+impl Resolver<'_> {
+    fn push(&mut self,kind:ScopeKind) {
+        let parent = self.stack.last().map(|(scope,_)| *scope);
+        self.table.scopes.push(Scope {
+            kind,
+            parent,
+            symbols:vec![],
+        });
+        self.stack.push((self.table.scopes.len() - 1,vec![]));
+    }
+    fn pop(&mut self) {
+        self.stack.pop();
+    }
+    fn current(&self) -> usize {
+        self.stack.last().map(|(scope,_)| *scope).unwrap_or(0)
+    }
+    fn error(&mut self,span:Span,err:KzErr) {
+        self.errs.push(KzError::at(self.path,span,err));
+    }
+    fn declare(&mut self,name:&str,kind:SymbolKind,span:Span) {
+        let scope = self.current();
+        let duplicate = self.table.scopes[scope].symbols.iter().any(|id| self.table.symbols[*id].name == name);
+        if duplicate {
+            self.error(span,KzErr::Duplicate(name.to_string()));
+        }
+        if let Some((_,pending)) = self.stack.last_mut() {
+            if let Some(index) = pending.iter().position(|p| p == name) {
+                pending.remove(index);
+            }
+        }
+        self.table.symbols.push(Symbol {
+            name:name.to_string(),
+            kind,
+            span,
+            scope,
+        });
+        let id = self.table.symbols.len() - 1;
+        self.table.scopes[scope].symbols.push(id);
+    }
+    fn use_name(&mut self,name:&str,span:Span) {
+        if let Some(id) = self.table.lookup_id(self.current(),name) {
+            self.table.bindings.insert(span,id);
+            return
+        }
+        if self.stack.iter().any(|(_,pending)| pending.iter().any(|p| p == name)) {
+            self.error(span,KzErr::UseBeforeDeclaration(name.to_string()));
+        } else {
+            self.error(span,KzErr::Undefined(name.to_string()));
+        }
+    }
+    //a new scope over stems, its funs and types are declared up front
+    fn block(&mut self,kind:ScopeKind,stems:&[Statement]) {
+        self.push(kind);
+        self.stems(stems);
+        self.pop();
+    }
+    fn stems(&mut self,stems:&[Statement]) {
+        for stem in stems {
+            let inner = match &stem.kind {
+                StatementKind::Pub(inner) => inner,
+                _ => stem
+            };
+            match &inner.kind {
+                StatementKind::Fun(f) => {
+                    if let Some(Expression { kind:ExpressionKind::Ident(name), span }) = &f.name {
+                        self.declare(name,SymbolKind::Fun,*span);
+                    }
+                },
+                StatementKind::Type(t) => self.declare(&t.name,SymbolKind::Type,inner.span),
+                StatementKind::Let(l) | StatementKind::Const(l) => {
+                    let names:Vec<String> = l.names().iter().map(|n| n.to_string()).collect();
+                    if let Some((_,pending)) = self.stack.last_mut() {
+                        pending.extend(names);
+                    }
+                },
+                _ => {}
+            }
+        }
+        for stem in stems {
+            self.visit_statement(stem);
+        }
+    }
+    fn fun(&mut self,f:&FunExpression) {
+        //defaults are evaluated where the fun is declared
+        for exp in f.param_exp.iter().flatten() {
+            if let ExpressionKind::Param(param) = &exp.kind {
+                if let Some(default) = &param.default {
+                    self.visit_expression(default);
+                }
+            }
+        }
+        self.push(ScopeKind::Function);
+        for exp in f.param_exp.iter().flatten() {
+            if let ExpressionKind::Param(param) = &exp.kind {
+                self.declare(&param.name,SymbolKind::Param,exp.span);
+            }
+        }
+        self.stems(&f.body);
+        self.pop();
+    }
+}
+
+impl Visitor<'_> for Resolver<'_> {
+    fn visit_statement(&mut self,stem:&Statement) {
+        match &stem.kind {
+            StatementKind::Let(l) | StatementKind::Const(l) => {
+                if let Some(exp) = &l.exp {
+                    self.visit_expression(exp);
+                }
+                let kind = if matches!(stem.kind,StatementKind::Const(_)) { SymbolKind::Const } else { SymbolKind::Let };
+                for name in l.names() {
+                    self.declare(name,kind,stem.span);
+                }
+            },
+            StatementKind::IF(i) => {
+                self.visit_expression(&i.condition);
+                match &i.binding {
+                    Some(name) => {
+                        self.push(ScopeKind::IfLet);
+                        self.declare(name,SymbolKind::Binding,stem.span);
+                        self.block(ScopeKind::Block,&i.consequence);
+                        self.pop();
+                    },
+                    None => self.block(ScopeKind::Block,&i.consequence)
+                }
+                self.block(ScopeKind::Block,&i.alternative);
+            },
+            StatementKind::For(f) => {
+                self.push(ScopeKind::ForInit);
+                self.visit_statement(&f.start_condition);
+                self.visit_expression(&f.condition);
+                self.visit_statement(&f.self_operation);
+                self.block(ScopeKind::Block,&f.consequence);
+                self.pop();
+            },
+            //declared by the enclosing block
+            StatementKind::Fun(f) => self.fun(f),
+            StatementKind::Type(_) => {},
+            StatementKind::Import(import) => {
+                if import.names.is_empty() {
+                    let file = import.file();
+                    let name = Path::new(&file).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+                    self.declare(&name,SymbolKind::Import,stem.span);
+                }
+                for name in &import.names {
+                    self.declare(name,SymbolKind::Import,stem.span);
+                }
+            },
+            StatementKind::Defer(stems) => self.block(ScopeKind::Block,stems),
+            _ => walk_statement(self,stem)
+        }
+    }
+    fn visit_expression(&mut self,exp:&Expression) {
+        match &exp.kind {
+            ExpressionKind::Ident(name) => self.use_name(name,exp.span),
+            ExpressionKind::Fun(f) => self.fun(f),
+            //field names and struct members are not variables
+            ExpressionKind::Struct(_) | ExpressionKind::FunType(_,_) => {},
+            _ => walk_expression(self,exp)
+        }
+    }
+}
+
+#[test]
+fn test_resolve(){
+    let source = "let g:int = 1\nfun f(a:int) -> int {\n    let b = a + g + h(a)\n    for let i:int = 0;i < b;i++ {\n        if let x = m {\n            return x + i\n        }\n    }\n    return b\n}\nfun h(n:int) -> int {\n    return n\n}\n";
+    let stems = crate::parse_str(source).statements;
+    let (table,errs) = resolve("r.kz",&stems);
+    let errs:Vec<&KzErr> = errs.iter().map(|e| e.err()).collect();
+    assert_eq!(errs,vec![&KzErr::Undefined("m".to_string())]);
+
+    //every use of a points at the parameter
+    let a = table.symbols.iter().position(|s| s.name == "a").unwrap();
+    assert_eq!(table.symbols[a].kind,SymbolKind::Param);
+    assert_eq!(table.scopes[table.symbols[a].scope].kind,ScopeKind::Function);
+    let uses:Vec<(i32,i32)> = table.references(a).iter().map(|s| (s.line,s.col)).collect();
+    assert_eq!(uses,vec![(3,13),(3,23)]);
+
+    //h is called above its fun, funs are visible in their whole block
+    let h = table.symbols.iter().position(|s| s.name == "h").unwrap();
+    assert_eq!(table.symbols[h].scope,0);
+    assert_eq!(table.references(h).len(),1);
+
+    let i = table.symbols.iter().find(|s| s.name == "i").unwrap();
+    assert_eq!(table.scopes[i.scope].kind,ScopeKind::ForInit);
+    let x = table.symbols.iter().find(|s| s.name == "x").unwrap();
+    assert_eq!(x.kind,SymbolKind::Binding);
+    assert_eq!(table.scopes[x.scope].kind,ScopeKind::IfLet);
+    assert_eq!(table.scopes[table.scopes[x.scope].parent.unwrap()].kind,ScopeKind::Block);
+    assert_eq!(table.lookup(x.scope,"g").unwrap().kind,SymbolKind::Let);
+    assert!(table.lookup(0,"x").is_none());
+}
+#[test]
+fn test_resolve_errors(){
+    //the body of an if let may shadow its binding, as the body of a for its init
+    let source = "let a:int = b\nlet b:int = 1\nlet b:int = 2\nfun f(p:int, p:int) {\n    let q = p\n    let q = r\n}\nlet c = |y| y + z\nlet d = d\nif true {\n    let a:int = 3\n}\n\
+        if let x = c {\n    let x = 1\n}\n";
+    let stems = crate::parse_str(source).statements;
+    let (_,errs) = resolve("r.kz",&stems);
+    let errs:Vec<(i32,&KzErr)> = errs.iter().map(|e| (e.span().line,e.err())).collect();
+    assert_eq!(errs,vec![
+        (1,&KzErr::UseBeforeDeclaration("b".to_string())),
+        (3,&KzErr::Duplicate("b".to_string())),
+        (4,&KzErr::Duplicate("p".to_string())),
+        (6,&KzErr::Undefined("r".to_string())),
+        (6,&KzErr::Duplicate("q".to_string())),
+        (8,&KzErr::Undefined("z".to_string())),
+        (9,&KzErr::UseBeforeDeclaration("d".to_string())),
+    ]);
+}
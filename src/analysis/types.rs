@@ -0,0 +1,811 @@
+use std::collections::HashMap;
+
+use crate::analysis::propagate::carries_error;
+use crate::analysis::resolve::{SymbolKind, SymbolTable};
+use crate::ast::fun::FunExpression;
+use crate::ast::node::{Expression, ExpressionKind, Statement, StatementKind};
+use crate::ast::visit::{Visitor, walk_fun, walk_program, walk_statement};
+use crate::parser::error::{KzErr, KzError};
+use crate::parser::span::Span;
+use crate::parser::token::Token;
+
+//types are the type tokens of the parser, Token::Unknown is a type that is not known
+//and matches everything, Token::Nil is the type of nil
+
+//the type of every expression, let and param by its span, and of every symbol by its id,
+//filled in by inference where the source leaves them out. nodes whose type stayed
+//unknown have no entry
+#[derive(Debug,Default)]
+pub struct TypeTable {
+    nodes:HashMap<Span,Token>,
+    symbols:HashMap<usize,Token>,
+}
+impl TypeTable {
+    pub fn type_of(&self,span:Span) -> Option<&Token> {
+        self.nodes.get(&span)
+    }
+    pub fn symbol(&self,id:usize) -> Option<&Token> {
+        self.symbols.get(&id)
+    }
+    //the innermost typed node around a byte offset, what a hover shows
+    pub fn type_at(&self,offset:usize) -> Option<(Span,&Token)> {
+        self.nodes.iter()
+            .filter(|(span,_)| span.start <= offset && offset < span.end)
+            .min_by_key(|(span,_)| span.len())
+            .map(|(span,tok)| (*span,tok))
+    }
+}
+
+#[derive(Clone)]
+struct Param {
+    name:String,
+    token_type:Token,
+    default:bool,
+    variadic:bool,
+    //symbol of the param, none for the params of a fun type
+    id:Option<usize>,
+}
+#[derive(Clone)]
+struct Signature {
+    name:String,
+    params:Vec<Param>,
+    ret:Token,
+    //the fun itself, inferred lambda returns are kept by it
+    span:Span,
+}
+
+struct TypeCheck<'a> {
+    path:&'a str,
+    symbols:&'a SymbolTable,
+    //symbol id of a declaration, by its span and name
+    declared:HashMap<(Span,String),usize>,
+    //type of every symbol, kept from one pass to the next
+    types:HashMap<usize,Token>,
+    //return types found in the body of lambdas, by the span of the lambda
+    lambda_rets:HashMap<Span,Token>,
+    nodes:HashMap<Span,Token>,
+    //funs, and lets holding a lambda, by symbol id
+    sigs:HashMap<usize,Signature>,
+    //type alias -> aliased type, struct -> fields, fun type -> signature
+    aliases:HashMap<String,Token>,
+    structs:HashMap<String,Vec<(String,Token)>>,
+    fun_types:HashMap<String,Signature>,
+    //declared and found return types of the funs being walked, innermost last
+    rets:Vec<(Option<Token>,Token)>,
+    //the fun type a lambda is expected to have, taken by the lambda walked next
+    context:Option<Signature>,
+    errs:Vec<KzError>,
+}
+
+//a type found late, `let a = []` filled in by `a[0] = 1` further down, reaches
+//the uses above it on the next pass. passes stop once nothing new is learned
+const PASSES:usize = 4;
+
+//let annotations against their initializers, operands of infix operators,
+//call arguments against parameters, returns against the declared return type,
+//indexing and field access
+pub fn check(path:&str,stems:&[Statement],symbols:&SymbolTable) -> Vec<KzError> {
+    infer(path,stems,symbols).1
+}
+//check, with the types of unannotated lets, lambda params and empty arrays inferred
+//from their initializers and uses, local to the file
+pub fn infer(path:&str,stems:&[Statement],symbols:&SymbolTable) -> (TypeTable,Vec<KzError>) {
+    let declared = symbols.symbols.iter().enumerate()
+        .map(|(id,symbol)| ((symbol.span,symbol.name.clone()),id))
+        .collect();
+    let mut t = TypeCheck {
+        path,
+        symbols,
+        declared,
+        types:HashMap::new(),
+        lambda_rets:HashMap::new(),
+        nodes:HashMap::new(),
+        sigs:HashMap::new(),
+        aliases:HashMap::new(),
+        structs:HashMap::new(),
+        fun_types:HashMap::new(),
+        rets:vec![],
+        context:None,
+        errs:vec![],
+    };
+    let mut c = Collect(&mut t);
+    walk_program(&mut c,stems);
+    for _ in 0..PASSES {
+        let types = t.types.clone();
+        let lambda_rets = t.lambda_rets.clone();
+        t.errs.clear();
+        t.nodes.clear();
+        walk_program(&mut t,stems);
+        if t.types == types && t.lambda_rets == lambda_rets {
+            break
+        }
+    }
+    (TypeTable { nodes:t.nodes, symbols:t.types },t.errs)
+}
+
+//int can be used where a float is expected, nil where an optional or a named type is
+pub fn assignable(expected:&Token,found:&Token) -> bool {
+    match (expected,found) {
+        (Token::Unknown,_) | (_,Token::Unknown) => true,
+        (Token::Optional(_),Token::Nil) | (Token::Ident(_),Token::Nil) => true,
+        (Token::Optional(a),Token::Optional(b)) => assignable(a,b),
+        (Token::Optional(a),b) => assignable(a,b),
+        (Token::Float,Token::Int) => true,
+        (Token::Array(n,a),Token::Array(m,b)) => (*n == 0 || *m == 0 || n == m) && assignable(a,b),
+        (Token::Tuple(a),Token::Tuple(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a,b)| assignable(a,b)),
+        (a,b) => a == b
+    }
+}
+//what both sides know about a type, a wins where both know something
+fn merge(a:&Token,b:&Token) -> Token {
+    match (a,b) {
+        (Token::Unknown,tok) | (tok,Token::Unknown) => tok.clone(),
+        (Token::Array(n,a),Token::Array(_,b)) => Token::Array(*n,Box::new(merge(a,b))),
+        (Token::Optional(a),Token::Optional(b)) => Token::Optional(Box::new(merge(a,b))),
+        (Token::Tuple(a),Token::Tuple(b)) if a.len() == b.len() => {
+            Token::Tuple(a.iter().zip(b).map(|(a,b)| merge(a,b)).collect())
+        },
+        (tok,_) => tok.clone()
+    }
+}
+//the named types in tok
+fn names(tok:&Token) -> Vec<String> {
+    match tok {
+        Token::Ident(name) => vec![name.clone()],
+        Token::Array(_,tok) | Token::Optional(tok) => names(tok),
+        Token::Tuple(toks) => toks.iter().flat_map(names).collect(),
+        _ => vec![]
+    }
+}
+fn numeric(tok:&Token) -> bool {
+    matches!(tok,Token::Int | Token::Float | Token::Unknown)
+}
+
+impl TypeCheck<'_> {
+    fn error(&mut self,span:Span,err:KzErr) {
+        self.errs.push(KzError::at(self.path,span,err));
+    }
+    fn symbol(&self,span:Span,name:&str) -> Option<usize> {
+        self.declared.get(&(span,name.to_string())).copied()
+    }
+    fn symbol_type(&self,id:usize) -> Token {
+        self.types.get(&id).cloned().unwrap_or(Token::Unknown)
+    }
+    //a declaration, what an earlier pass inferred fills in what tok leaves out
+    fn declare(&mut self,id:usize,tok:Token) {
+        let tok = merge(&tok,&self.symbol_type(id));
+        self.types.insert(id,tok);
+    }
+    //a use of exp that needs tok, an unannotated variable learns its type from it
+    fn refine(&mut self,exp:&Expression,tok:&Token) {
+        if matches!(tok,Token::Unknown | Token::Nil) {
+            return
+        }
+        match &exp.kind {
+            ExpressionKind::Ident(_) => {
+                let id = match self.symbols.binding_id(exp.span) {
+                    Some(id) => id,
+                    None => return
+                };
+                if matches!(self.symbols.symbols[id].kind,SymbolKind::Let | SymbolKind::Param | SymbolKind::Binding) {
+                    let tok = merge(&self.symbol_type(id),tok);
+                    self.types.insert(id,tok);
+                }
+            },
+            //a[0] = 1
+            ExpressionKind::Index(left,_) => self.refine(left,&Token::Array(0,Box::new(tok.clone()))),
+            _ => {}
+        }
+    }
+    //aliases replaced by what they name, bool has no keyword and is lexed as a name
+    fn normalize(&self,tok:&Token) -> Token {
+        self.normalize_in(tok,&mut vec![])
+    }
+    //seen holds the aliases being replaced, one that names itself again is a cycle,
+    //reported where it is declared, see alias_cycle
+    fn normalize_in(&self,tok:&Token,seen:&mut Vec<String>) -> Token {
+        match tok {
+            Token::Ident(name) => match self.aliases.get(name) {
+                Some(_) if seen.contains(name) => Token::Unknown,
+                Some(alias) => {
+                    seen.push(name.clone());
+                    let tok = self.normalize_in(alias,seen);
+                    seen.pop();
+                    tok
+                },
+                None if name == "bool" => Token::Bool,
+                None => tok.clone()
+            },
+            Token::Array(n,tok) => Token::Array(*n,Box::new(self.normalize_in(tok,seen))),
+            Token::Optional(tok) => Token::Optional(Box::new(self.normalize_in(tok,seen))),
+            Token::Tuple(toks) => Token::Tuple(toks.iter().map(|tok| self.normalize_in(tok,seen)).collect()),
+            _ => tok.clone()
+        }
+    }
+    //the aliases from name back to itself, type a b with type b a
+    fn alias_cycle(&self,name:&str) -> Option<Vec<String>> {
+        let mut chain = vec![name.to_string()];
+        self.reaches(name,&mut chain).then_some(chain)
+    }
+    fn reaches(&self,target:&str,chain:&mut Vec<String>) -> bool {
+        let tok = match chain.last().and_then(|last| self.aliases.get(last)) {
+            Some(tok) => tok,
+            None => return false
+        };
+        for name in names(tok) {
+            if name == target {
+                chain.push(name);
+                return true
+            }
+            if chain.contains(&name) {
+                continue
+            }
+            chain.push(name);
+            if self.reaches(target,chain) {
+                return true
+            }
+            chain.pop();
+        }
+        false
+    }
+    //exp where a value of type expected is needed, the expected type flows into
+    //lambdas, empty arrays and unannotated variables
+    fn expect(&mut self,expected:&Token,exp:&Expression) -> Token {
+        if let (ExpressionKind::Fun(f),Token::Ident(name)) = (&exp.kind,expected) {
+            if f.lambda {
+                self.context = self.fun_types.get(name).cloned();
+            }
+        }
+        let found = self.expression(exp);
+        let expected = self.normalize(expected);
+        if !assignable(&expected,&found) {
+            self.error(exp.span,KzErr::TypeMismatch(Box::new(expected),Box::new(found.clone())));
+            return found
+        }
+        self.refine(exp,&expected);
+        let tok = merge(&found,&expected);
+        if tok != Token::Unknown && !matches!(exp.kind,ExpressionKind::Fun(_)) {
+            self.nodes.insert(exp.span,tok);
+        }
+        found
+    }
+    fn params<'e>(&self,exps:impl Iterator<Item = &'e Expression>) -> Vec<Param> {
+        exps.filter_map(|exp| match &exp.kind {
+            ExpressionKind::Param(p) => Some(Param {
+                name:p.name.clone(),
+                token_type:p.token_type.clone(),
+                default:p.default.is_some(),
+                variadic:p.variadic,
+                id:self.symbol(exp.span,&p.name),
+            }),
+            _ => None
+        }).collect()
+    }
+    fn signature(&self,f:&FunExpression,name:String,span:Span) -> Signature {
+        Signature {
+            name,
+            params:self.params(f.param_exp.iter().flatten()),
+            ret:f.ret_type.clone().unwrap_or(Token::Unknown),
+            span,
+        }
+    }
+    fn fun(&mut self,f:&FunExpression,span:Span) {
+        let context = if f.lambda { self.context.take() } else { None };
+        for (i,exp) in f.param_exp.iter().flatten().enumerate() {
+            if let ExpressionKind::Param(p) = &exp.kind {
+                if let Some(default) = &p.default {
+                    self.expect(&p.token_type,default);
+                }
+                if let Some(id) = self.symbol(exp.span,&p.name) {
+                    let mut tok = self.normalize(&p.token_type);
+                    //|x| x + 1 passed where a fun(a:int) is expected
+                    if let Some(p) = context.as_ref().and_then(|sig| sig.params.get(i)) {
+                        tok = merge(&tok,&self.normalize(&p.token_type));
+                    }
+                    self.declare(id,tok);
+                    let tok = self.symbol_type(id);
+                    if tok != Token::Unknown {
+                        self.nodes.insert(exp.span,tok);
+                    }
+                }
+            }
+        }
+        //only a lambda infers its return type, a fun without one is void
+        let declared = if f.lambda { None } else { Some(f.ret_type.as_ref().map(|tok| self.normalize(tok)).unwrap_or(Token::Void)) };
+        self.rets.push((declared,Token::Unknown));
+        walk_fun(self,f);
+        let (_,found) = self.rets.pop().unwrap();
+        if f.lambda {
+            let mut tok = merge(&f.ret_type.clone().unwrap_or(Token::Unknown),&found);
+            if let Some(sig) = &context {
+                tok = merge(&tok,&self.normalize(&sig.ret));
+            }
+            self.lambda_rets.insert(span,tok);
+        }
+    }
+    fn expression(&mut self,exp:&Expression) -> Token {
+        let tok = self.expression_type(exp);
+        if tok != Token::Unknown {
+            self.nodes.insert(exp.span,tok.clone());
+        }
+        tok
+    }
+    fn expression_type(&mut self,exp:&Expression) -> Token {
+        match &exp.kind {
+            ExpressionKind::Int(_) => Token::Int,
+            ExpressionKind::Float(_) => Token::Float,
+            ExpressionKind::String(_) => Token::String,
+            ExpressionKind::Bool(_) => Token::Bool,
+            ExpressionKind::Nil => Token::Nil,
+            ExpressionKind::Ident(_) => self.symbols.binding_id(exp.span)
+                .map(|id| self.symbol_type(id))
+                .unwrap_or(Token::Unknown),
+            ExpressionKind::Infix(infix) => {
+                let mut left = self.expression(&infix.left);
+                let mut right = self.expression(&infix.right);
+                let op = &infix.op_symbol;
+                //x * 2 makes x an int, a && b makes both bools
+                match op {
+                    Token::And | Token::Or => {
+                        self.refine(&infix.left,&Token::Bool);
+                        self.refine(&infix.right,&Token::Bool);
+                    },
+                    Token::Add | Token::Sub | Token::Mul | Token::Div
+                    | Token::LT | Token::GT | Token::LTEqu | Token::GTEqu => {
+                        if left == Token::Unknown && matches!(right,Token::Int | Token::Float | Token::String) {
+                            self.refine(&infix.left,&right);
+                            left = right.clone();
+                        } else if right == Token::Unknown && matches!(left,Token::Int | Token::Float | Token::String) {
+                            self.refine(&infix.right,&left);
+                            right = left.clone();
+                        }
+                    },
+                    _ => {}
+                }
+                let tok = match op {
+                    Token::Add if left == Token::String && right == Token::String => Some(Token::String),
+                    Token::Add | Token::Sub | Token::Mul | Token::Div if numeric(&left) && numeric(&right) => {
+                        if left == Token::Unknown || right == Token::Unknown {
+                            Some(Token::Unknown)
+                        } else if left == Token::Float || right == Token::Float {
+                            Some(Token::Float)
+                        } else {
+                            Some(Token::Int)
+                        }
+                    },
+                    Token::LT | Token::GT | Token::LTEqu | Token::GTEqu
+                        if (numeric(&left) && numeric(&right)) || (left == Token::String && right == Token::String)
+                        || left == Token::Unknown || right == Token::Unknown => Some(Token::Bool),
+                    Token::Equ | Token::BangEqu if assignable(&left,&right) || assignable(&right,&left) => Some(Token::Bool),
+                    Token::And | Token::Or
+                        if matches!(left,Token::Bool | Token::Unknown) && matches!(right,Token::Bool | Token::Unknown) => Some(Token::Bool),
+                    _ if left == Token::Unknown || right == Token::Unknown => Some(Token::Unknown),
+                    _ => None
+                };
+                match tok {
+                    Some(tok) => tok,
+                    None => {
+                        self.error(exp.span,KzErr::Operand(op.clone(),Box::new(left),Box::new(right)));
+                        Token::Unknown
+                    }
+                }
+            },
+            //[] is an array of unknown elements until something is put in it
+            ExpressionKind::ListValue(list) => {
+                let toks:Vec<Token> = list.iter().map(|exp| self.expression(exp)).collect();
+                let elem = toks.into_iter().find(|tok| *tok != Token::Unknown && *tok != Token::Nil).unwrap_or(Token::Unknown);
+                Token::Array(0,Box::new(elem))
+            },
+            ExpressionKind::Tuple(list) => Token::Tuple(list.iter().map(|exp| self.expression(exp)).collect()),
+            ExpressionKind::Call(f,args) => self.call(exp.span,f,args),
+            ExpressionKind::Index(left,index) => {
+                let target = self.expression(left);
+                self.expect(&Token::Int,index);
+                match target {
+                    Token::Array(_,tok) => *tok,
+                    Token::Unknown => Token::Unknown,
+                    tok => {
+                        self.error(left.span,KzErr::Index(tok));
+                        Token::Unknown
+                    }
+                }
+            },
+            ExpressionKind::Field(left,name) => {
+                let target = self.expression(left);
+                let fields = match &target {
+                    Token::Ident(ty) => self.structs.get(ty),
+                    _ => None
+                };
+                match fields {
+                    Some(fields) => match fields.iter().find(|(field,_)| field == name) {
+                        Some((_,tok)) => self.normalize(tok),
+                        None => {
+                            self.error(exp.span,KzErr::NoField(Box::new(target),name.clone()));
+                            Token::Unknown
+                        }
+                    },
+                    None => Token::Unknown
+                }
+            },
+            ExpressionKind::SelfOp(exp,_,_) | ExpressionKind::NamedArg(_,exp) => self.expression(exp),
+            //f()? is the value without its trailing error
+            ExpressionKind::Try(exp) => match self.expression(exp) {
+                Token::Tuple(mut toks) if toks.last().is_some_and(carries_error) => {
+                    toks.pop();
+                    if toks.len() == 1 { toks.remove(0) } else { Token::Tuple(toks) }
+                },
+                _ => Token::Unknown
+            },
+            ExpressionKind::Fun(f) => {
+                self.fun(f,exp.span);
+                Token::Unknown
+            },
+            ExpressionKind::Param(_) | ExpressionKind::Struct(_) | ExpressionKind::FunType(_,_)
+            | ExpressionKind::Basic(_) | ExpressionKind::Unknown => Token::Unknown,
+        }
+    }
+    //a param without a type takes the one inferred for it
+    fn param_type(&self,p:&Param) -> Token {
+        match p.id {
+            Some(id) if p.token_type == Token::Unknown => self.symbol_type(id),
+            _ => p.token_type.clone()
+        }
+    }
+    //an argument passed to a param without a type, |x| x called as f(1) makes x an int
+    fn argument(&mut self,p:&Param,tok:&Token,arg:&Expression) {
+        let found = self.expect(tok,arg);
+        if let Some(id) = p.id {
+            if found != Token::Nil {
+                let tok = merge(&self.symbol_type(id),&found);
+                self.types.insert(id,tok);
+            }
+        }
+    }
+    fn call(&mut self,span:Span,f:&Expression,args:&[Expression]) -> Token {
+        //a fun or lambda by name, or a variable of a fun type
+        let sig = match &f.kind {
+            ExpressionKind::Ident(_) => self.symbols.binding_id(f.span).and_then(|id| match self.sigs.get(&id) {
+                Some(sig) => Some(sig.clone()),
+                None => match self.types.get(&id) {
+                    Some(Token::Ident(name)) => self.fun_types.get(name).cloned(),
+                    _ => None
+                }
+            }),
+            _ => None
+        };
+        let sig = match sig {
+            Some(sig) => sig,
+            None => {
+                self.expression(f);
+                for arg in args {
+                    self.expression(arg);
+                }
+                return Token::Unknown
+            }
+        };
+        let variadic = sig.params.last().is_some_and(|p| p.variadic);
+        let fixed = if variadic { sig.params.len() - 1 } else { sig.params.len() };
+        let required = sig.params.iter().filter(|p| !p.default && !p.variadic).count();
+        let mut positional = 0;
+        for arg in args {
+            match &arg.kind {
+                ExpressionKind::NamedArg(name,exp) => match sig.params.iter().find(|p| p.name == *name) {
+                    Some(p) => {
+                        let tok = self.param_type(p);
+                        self.argument(p,&tok,exp);
+                    },
+                    None => {
+                        self.error(arg.span,KzErr::ArgName(sig.name.clone(),name.clone()));
+                        self.expression(exp);
+                    }
+                },
+                _ => {
+                    match sig.params.get(positional) {
+                        Some(p) if positional < fixed => {
+                            let tok = self.param_type(p);
+                            self.argument(p,&tok,arg);
+                        },
+                        _ if variadic => {
+                            let elem = match &sig.params[fixed].token_type {
+                                Token::Array(_,tok) => *tok.clone(),
+                                tok => tok.clone()
+                            };
+                            self.expect(&elem,arg);
+                        },
+                        _ => {
+                            self.expression(arg);
+                        }
+                    }
+                    positional += 1;
+                }
+            }
+        }
+        let named = args.iter().filter(|arg| matches!(arg.kind,ExpressionKind::NamedArg(_,_))).count();
+        if positional + named < required || (!variadic && positional > fixed) {
+            let expected = if positional + named < required { required } else { fixed };
+            self.error(span,KzErr::ArgCount(sig.name.clone(),expected,positional + named));
+        }
+        let ret = merge(&sig.ret,&self.lambda_rets.get(&sig.span).cloned().unwrap_or(Token::Unknown));
+        self.normalize(&ret)
+    }
+}
+
+impl Visitor<'_> for TypeCheck<'_> {
+    fn visit_statement(&mut self,stem:&Statement) {
+        match &stem.kind {
+            StatementKind::Let(l) | StatementKind::Const(l) => {
+                let declared = self.normalize(&l.token_type);
+                //let x = 5 is an int, let x = nil says nothing
+                let found = match &l.exp {
+                    Some(exp) if declared != Token::Unknown => {
+                        self.expect(&declared,exp);
+                        declared.clone()
+                    },
+                    Some(exp) => match self.expression(exp) {
+                        Token::Nil => Token::Unknown,
+                        tok => tok
+                    },
+                    None => declared.clone()
+                };
+                if l.is_tuple() {
+                    let fits = matches!(&found,Token::Tuple(toks) if toks.len() == l.tuple.len());
+                    if found != Token::Unknown && !fits {
+                        self.error(stem.span,KzErr::Destructure(l.tuple.len(),Box::new(found.clone())));
+                    }
+                    let mut toks = vec![];
+                    for (i,name) in l.tuple.iter().enumerate() {
+                        let tok = match &found {
+                            Token::Tuple(toks) => toks.get(i).cloned().unwrap_or(Token::Unknown),
+                            _ => Token::Unknown
+                        };
+                        if let Some(id) = self.symbol(stem.span,name) {
+                            self.declare(id,tok);
+                            toks.push(self.symbol_type(id));
+                        }
+                    }
+                    self.nodes.insert(stem.span,Token::Tuple(toks));
+                } else if let Some(id) = self.symbol(stem.span,&l.ident) {
+                    self.declare(id,found);
+                    let tok = self.symbol_type(id);
+                    if tok != Token::Unknown {
+                        self.nodes.insert(stem.span,tok);
+                    }
+                }
+            },
+            StatementKind::Assign(target,exp) => {
+                let tok = self.expression(target);
+                let found = self.expect(&tok,exp);
+                if found != Token::Nil {
+                    self.refine(target,&found);
+                }
+            },
+            StatementKind::IF(i) => {
+                let tok = self.expression(&i.condition);
+                if let Some(name) = &i.binding {
+                    let tok = match tok {
+                        Token::Optional(tok) => *tok,
+                        tok => tok
+                    };
+                    if let Some(id) = self.symbol(stem.span,name) {
+                        self.declare(id,tok);
+                    }
+                }
+                walk_program(self,&i.consequence);
+                walk_program(self,&i.alternative);
+            },
+            StatementKind::Return(None) => match self.rets.last().and_then(|ret| ret.0.clone()) {
+                Some(ret) if ret != Token::Unknown && ret != Token::Void => self.error(stem.span,KzErr::TypeMismatch(Box::new(ret),Box::new(Token::Void))),
+                _ => {}
+            },
+            StatementKind::Return(Some(exp)) => match self.rets.last().and_then(|ret| ret.0.clone()) {
+                Some(Token::Void) => {
+                    let found = self.expression(exp);
+                    self.error(exp.span,KzErr::TypeMismatch(Box::new(Token::Void),Box::new(found)));
+                },
+                Some(ret) => {
+                    self.expect(&ret,exp);
+                },
+                None => {
+                    let tok = self.expression(exp);
+                    if let Some(ret) = self.rets.last_mut() {
+                        ret.1 = merge(&ret.1,&tok);
+                    }
+                }
+            },
+            StatementKind::Fun(f) => self.fun(f,stem.span),
+            StatementKind::Type(t) => {
+                if let Some(chain) = self.alias_cycle(&t.name) {
+                    self.error(stem.span,KzErr::AliasCycle(chain));
+                }
+            },
+            _ => walk_statement(self,stem)
+        }
+    }
+    fn visit_expression(&mut self,exp:&Expression) {
+        self.expression(exp);
+    }
+}
+
+//signatures and named types up front, a fun can be called above its declaration
+struct Collect<'a,'b>(&'a mut TypeCheck<'b>);
+
+impl Visitor<'_> for Collect<'_,'_> {
+    fn visit_statement(&mut self,stem:&Statement) {
+        match &stem.kind {
+            StatementKind::Fun(f) => {
+                if let Some(Expression { kind:ExpressionKind::Ident(name), span }) = &f.name {
+                    if let Some(id) = self.0.symbol(*span,name) {
+                        let sig = self.0.signature(f,name.clone(),stem.span);
+                        self.0.sigs.insert(id,sig);
+                    }
+                }
+            },
+            StatementKind::Let(l) => {
+                if let Some(Expression { kind:ExpressionKind::Fun(f), span }) = &l.exp {
+                    if let Some(id) = self.0.symbol(stem.span,&l.ident) {
+                        let sig = self.0.signature(f,l.ident.clone(),*span);
+                        self.0.sigs.insert(id,sig);
+                    }
+                }
+            },
+            StatementKind::Type(t) => match &t.exp.kind {
+                ExpressionKind::Struct(fields) => {
+                    let fields = fields.iter().filter_map(|exp| match &exp.kind {
+                        ExpressionKind::Param(p) => Some((p.name.clone(),p.token_type.clone())),
+                        _ => None
+                    }).collect();
+                    self.0.structs.insert(t.name.clone(),fields);
+                },
+                ExpressionKind::Basic(tok) => {
+                    self.0.aliases.insert(t.name.clone(),tok.clone());
+                },
+                ExpressionKind::FunType(params,ret) => {
+                    let sig = Signature {
+                        name:t.name.clone(),
+                        params:self.0.params(params.iter().flatten()),
+                        ret:ret.clone().unwrap_or(Token::Unknown),
+                        span:t.exp.span,
+                    };
+                    self.0.fun_types.insert(t.name.clone(),sig);
+                },
+                _ => {}
+            },
+            _ => {}
+        }
+        walk_statement(self,stem)
+    }
+}
+
+#[cfg(test)]
+fn mismatch(expected:Token,found:Token) -> KzErr {
+    KzErr::TypeMismatch(Box::new(expected),Box::new(found))
+}
+#[test]
+fn test_types_let(){
+    let source = "let c:int = \"test\" + \"b\"\nlet d:float = 1\nlet e:?int = nil\nlet f:[]int = [1,2]\nlet g:[]string = [1]\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements,&p.symbols)),vec![
+        (1,mismatch(Token::Int,Token::String)),
+        (5,mismatch(Token::Array(0,Box::new(Token::String)),Token::Array(0,Box::new(Token::Int)))),
+    ]);
+    //aliases and tuples
+    let source = "type id int\nlet a:id = 1\nlet (x,y):(int,string) = (1,\"a\")\nlet z:string = x\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements,&p.symbols)),vec![
+        (4,mismatch(Token::String,Token::Int)),
+    ]);
+    let source = "let (p,q,r) = (1,2)\nlet (a,b) = 5\nlet (c,d) = (1,2)\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements,&p.symbols)),vec![
+        (1,KzErr::Destructure(3,Box::new(Token::Tuple(vec![Token::Int,Token::Int])))),
+        (2,KzErr::Destructure(2,Box::new(Token::Int))),
+    ]);
+}
+#[test]
+fn test_types_infix(){
+    let source = "let a:int = 1\nlet s:string = \"a\"\nlet b = a + s\nlet c = a < 2.5 && s\nlet d = s == a\nlet e = s + s\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements,&p.symbols)),vec![
+        (3,KzErr::Operand(Token::Add,Box::new(Token::Int),Box::new(Token::String))),
+        (4,KzErr::Operand(Token::And,Box::new(Token::Bool),Box::new(Token::String))),
+        (5,KzErr::Operand(Token::Equ,Box::new(Token::String),Box::new(Token::Int))),
+    ]);
+}
+#[test]
+fn test_types_call(){
+    let source = "fun log(msg:string, level:int = 1, ...args:[]string) {\n}\nlog(\"x\", level: \"3\")\nlog(\"x\", 2, \"a\", 3)\nlog()\nlog(\"x\", size: 1)\nfun add(a:int,b:int) -> int {\n    return a + b\n}\nlet s:string = add(1,2,3)\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements,&p.symbols)),vec![
+        (3,mismatch(Token::Int,Token::String)),
+        (4,mismatch(Token::String,Token::Int)),
+        (5,KzErr::ArgCount("log".to_string(),1,0)),
+        (6,KzErr::ArgName("log".to_string(),"size".to_string())),
+        (10,KzErr::ArgCount("add".to_string(),2,3)),
+        (10,mismatch(Token::String,Token::Int)),
+    ]);
+}
+#[test]
+fn test_types_return_index_field(){
+    let source = "type point struct {\n    x:int\n}\nfun f(p:point, a:[]int) -> string {\n    let b:int = a[\"0\"]\n    let c:int = p.x\n    let d = p.y\n    let e = b[0]\n    return c\n}\nfun g() -> ?int {\n    return nil\n}\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements,&p.symbols)),vec![
+        (5,mismatch(Token::Int,Token::String)),
+        (7,KzErr::NoField(Box::new(Token::Ident("point".to_string())),"y".to_string())),
+        (8,KzErr::Index(Token::Int)),
+        (9,mismatch(Token::String,Token::Int)),
+    ]);
+}
+#[test]
+fn test_types_bare_return(){
+    let source = "fun f(x:int) {\n    if x > 0 {\n        return\n    }\n    let y = x\n}\nfun g() -> int {\n    return\n}\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements,&p.symbols)),vec![(8,mismatch(Token::Int,Token::Void))]);
+    //a fun without a return type is void, only lambdas infer theirs
+    let source = "fun f() {\n    return 1\n}\nlet g = fun () {\n    return \"a\"\n}\nlet h = || {\n    return 1\n}\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements,&p.symbols)),vec![
+        (2,mismatch(Token::Void,Token::Int)),
+        (5,mismatch(Token::Void,Token::String)),
+    ]);
+}
+#[test]
+fn test_types_bool(){
+    //bool is lexed as a name, literals and && give Token::Bool
+    let source = "let a:bool = true\nfun f(a:bool,b:bool) -> bool {\n    return a && b\n}\nlet c = f(a,false)\nlet d:bool = 1\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements,&p.symbols)),vec![(6,mismatch(Token::Bool,Token::Int))]);
+}
+#[test]
+fn test_types_alias_cycle(){
+    let chain = |names:&[&str]| KzErr::AliasCycle(names.iter().map(|s| s.to_string()).collect());
+    let source = "type a b\ntype b a\nlet z:a = 1\ntype c c\nlet y:c = 1\n";
+    assert_eq!(super::check_str(source,|p| check("t.kz",&p.statements,&p.symbols)),vec![
+        (1,chain(&["a","b","a"])),
+        (2,chain(&["b","a","b"])),
+        (4,chain(&["c","c"])),
+    ]);
+}
+#[cfg(test)]
+fn infer_str(source:&str) -> (crate::Program,TypeTable,Vec<(i32,KzErr)>) {
+    let program = crate::parse_str(source);
+    let (types,errs) = infer("t.kz",&program.statements,&program.symbols);
+    (program,types,super::lines(errs))
+}
+#[cfg(test)]
+fn let_type(program:&crate::Program,types:&TypeTable,i:usize) -> Token {
+    types.type_of(program.statements[i].span).cloned().unwrap_or(Token::Unknown)
+}
+#[test]
+fn test_infer_let(){
+    let (program,types,errs) = infer_str("let x = 5\nlet y = x * 2.5\nlet z = x\nz = \"a\"\nlet (a,b) = (1,\"b\")\n");
+    assert_eq!(let_type(&program,&types,0),Token::Int);
+    assert_eq!(let_type(&program,&types,1),Token::Float);
+    assert_eq!(let_type(&program,&types,2),Token::Int);
+    assert_eq!(let_type(&program,&types,4),Token::Tuple(vec![Token::Int,Token::String]));
+    assert_eq!(errs,vec![(4,mismatch(Token::Int,Token::String))]);
+}
+#[test]
+fn test_infer_empty_array(){
+    //filled in below its first use, seen there on the next pass
+    let (program,types,errs) = infer_str("let a = []\nlet first = a[0]\na[0] = 1\nlet b:[]float = []\nb[1] = \"x\"\n");
+    assert_eq!(let_type(&program,&types,0),Token::Array(0,Box::new(Token::Int)));
+    assert_eq!(let_type(&program,&types,1),Token::Int);
+    assert_eq!(errs,vec![(5,mismatch(Token::Float,Token::String))]);
+    let list = match &program.statements[3].kind {
+        StatementKind::Let(l) => l.exp.as_ref().unwrap().span,
+        _ => unreachable!()
+    };
+    assert_eq!(types.type_of(list),Some(&Token::Array(0,Box::new(Token::Float))));
+}
+#[test]
+fn test_infer_lambda(){
+    let source = "let double = |x| x * 2\nlet s:string = double(2)\nlet id = |v| v\nlet n:int = id(\"a\")\n\
+        type op fun(a:int) -> int\nfun apply(f:op) -> int {\n    return f(1)\n}\napply(|y| y + \"s\")\n";
+    let (program,types,errs) = infer_str(source);
+    assert_eq!(errs,vec![
+        (2,mismatch(Token::String,Token::Int)),
+        (4,mismatch(Token::Int,Token::String)),
+        (9,KzErr::Operand(Token::Add,Box::new(Token::Int),Box::new(Token::String))),
+    ]);
+    let x = program.symbols.symbols.iter().position(|symbol| symbol.name == "x").unwrap();
+    assert_eq!(types.symbol(x),Some(&Token::Int));
+}
+#[test]
+fn test_infer_hover(){
+    let source = "let count = 3\nlet total = count + 1\n";
+    let (_,types,_) = infer_str(source);
+    let offset = source.rfind("count").unwrap();
+    let (span,tok) = types.type_at(offset).unwrap();
+    assert_eq!((&source[span.start..span.end],tok),("count",&Token::Int));
+}
@@ -0,0 +1,413 @@
+//the tree as data for tools outside of rust, written without any dependency.
+//
+//json, one object per node:
+//  {"kind":"<name>","span":<span>,...fields}
+//  span   {"file":0,"start":4,"end":9,"line":1,"col":5}, start/end are byte offsets, end exclusive
+//  kind   StatementKind::name or ExpressionKind::name
+//  types  written as kz source, "[]?string" "(int,error)", see printer::type_name
+//  absent optional fields are null, absent lists are []
+//
+//  Program   body:[stmt] diagnostics:[Diagnostic], the root has no span
+//  Diagnostic  severity:"error"|"warning" message:str
+//  Let Const name:str|null tuple:[str] type:type|null value:expr|null doc:str|null
+//  Assign    target:expr value:expr
+//  If        binding:str|null condition:expr then:[stmt] else:[stmt]
+//  Fun       name:str|null params:[Param] ret:type|null body:[stmt] lambda:bool captures:[str] doc:str|null
+//  Type      name:str value:expr doc:str|null
+//  Invoke    value:expr
+//  Return    value:expr|null
+//  For       init:stmt condition:expr step:stmt body:[stmt]
+//  Import    path:str module:bool names:[str]
+//  Pub       item:stmt
+//  Defer     body:[stmt]
+//  Break Continue Error  no fields
+//
+//  Int Float Bool String  value:number|bool|str, a float always has a point and no
+//            exponent. it is finite, the parser rejects one out of f32 range
+//  Ident     name:str
+//  Infix     op:str left:expr right:expr
+//  Param     name:str type:type default:expr|null variadic:bool
+//  NamedArg  name:str value:expr
+//  Struct    fields:[Param]
+//  FunType   params:[Param]|null ret:type|null
+//  Basic     type:type
+//  ListValue Tuple  items:[expr]
+//  Call      callee:expr args:[expr]
+//  Index     target:expr index:expr
+//  Field     target:expr name:str
+//  SelfOp    target:expr op:str prefix:bool
+//  Fun       as the statement
+//  Try       value:expr
+//  Nil Unknown  no fields
+//
+//s-expressions carry the same nodes and fields without spans, null, false and
+//empty lists, so snapshots do not move when code shifts:
+//  (Let :name "a" :type "int" :value (Infix :op "+" :left (Ident :name "b") :right (Int :value 1)))
+use crate::ast::fun::FunExpression;
+use crate::ast::lets::LetStatement;
+use crate::ast::node::{Expression, ExpressionKind, Statement, StatementKind};
+use crate::ast::param::ParamExpression;
+use crate::ast::printer::{float, type_name};
+use crate::parser::error::{KzError, Severity};
+use crate::parser::span::Span;
+use crate::parser::token::Token;
+
+pub fn json(stems:&[Statement],diagnostics:&[KzError]) -> String {
+    let mut out = String::new();
+    write_json(&program(stems,diagnostics),&mut out);
+    out
+}
+pub fn sexp(stems:&[Statement]) -> String {
+    let mut out = String::new();
+    for (i,stem) in stems.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        write_sexp(&statement(stem),&mut out);
+    }
+    out
+}
+
+enum Value {
+    Null,
+    Bool(bool),
+    Number(String),
+    Str(String),
+    List(Vec<Value>),
+    Node(&'static str,Option<Span>,Vec<(&'static str,Value)>),
+}
+
+impl Value {
+    fn str(s:&str) -> Value {
+        Value::Str(s.to_string())
+    }
+    fn opt<T>(v:Option<T>,f:impl FnOnce(T) -> Value) -> Value {
+        v.map(f).unwrap_or(Value::Null)
+    }
+    fn strs(list:&[String]) -> Value {
+        Value::List(list.iter().map(|s| Value::str(s)).collect())
+    }
+    //omitted from s-expressions
+    fn is_empty(&self) -> bool {
+        match self {
+            Value::Null | Value::Bool(false) => true,
+            Value::List(list) => list.is_empty(),
+            _ => false
+        }
+    }
+}
+
+fn program(stems:&[Statement],diagnostics:&[KzError]) -> Value {
+    let diagnostics = diagnostics.iter().map(|e| {
+        let severity = match e.severity() {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        Value::Node("Diagnostic",Some(e.span()),vec![
+            ("severity",Value::Str(severity.to_string())),
+            ("message",Value::Str(e.err().echo())),
+        ])
+    }).collect();
+    Value::Node("Program",None,vec![
+        ("body",statements(stems)),
+        ("diagnostics",Value::List(diagnostics)),
+    ])
+}
+fn statements(stems:&[Statement]) -> Value {
+    Value::List(stems.iter().map(statement).collect())
+}
+fn expressions(list:&[Expression]) -> Value {
+    Value::List(list.iter().map(expression).collect())
+}
+fn ty(tok:&Token) -> Value {
+    if *tok == Token::Unknown {
+        return Value::Null
+    }
+    Value::Str(type_name(tok))
+}
+fn op(tok:&Token) -> Value {
+    Value::Str(format!("{:?}",tok))
+}
+fn statement(stem:&Statement) -> Value {
+    let fields = match &stem.kind {
+        StatementKind::Let(l) | StatementKind::Const(l) => let_(l),
+        StatementKind::Assign(target,exp) => vec![("target",expression(target)),("value",expression(exp))],
+        StatementKind::IF(i) => vec![
+            ("binding",Value::opt(i.binding.as_deref(),Value::str)),
+            ("condition",expression(&i.condition)),
+            ("then",statements(&i.consequence)),
+            ("else",statements(&i.alternative)),
+        ],
+        StatementKind::Fun(f) => fun(f),
+        StatementKind::Type(t) => vec![
+            ("name",Value::str(&t.name)),
+            ("value",expression(&t.exp)),
+            ("doc",Value::opt(t.doc.as_deref(),Value::str)),
+        ],
+        StatementKind::Invoke(exp) => vec![("value",expression(exp))],
+        StatementKind::Return(exp) => vec![("value",Value::opt(exp.as_ref(),expression))],
+        StatementKind::For(f) => vec![
+            ("init",statement(&f.start_condition)),
+            ("condition",expression(&f.condition)),
+            ("step",statement(&f.self_operation)),
+            ("body",statements(&f.consequence)),
+        ],
+        StatementKind::Import(import) => vec![
+            ("path",Value::str(&import.path)),
+            ("module",Value::Bool(import.module)),
+            ("names",Value::strs(&import.names)),
+        ],
+        StatementKind::Pub(stem) => vec![("item",statement(stem))],
+        StatementKind::Defer(stems) => vec![("body",statements(stems))],
+        StatementKind::Break | StatementKind::Continue | StatementKind::Error => vec![],
+    };
+    Value::Node(stem.kind.name(),Some(stem.span),fields)
+}
+fn let_(l:&LetStatement) -> Vec<(&'static str,Value)> {
+    let name = if l.is_tuple() { Value::Null } else { Value::str(&l.ident) };
+    vec![
+        ("name",name),
+        ("tuple",Value::strs(&l.tuple)),
+        ("type",ty(&l.token_type)),
+        ("value",Value::opt(l.exp.as_ref(),expression)),
+        ("doc",Value::opt(l.doc.as_deref(),Value::str)),
+    ]
+}
+fn fun(f:&FunExpression) -> Vec<(&'static str,Value)> {
+    let name = f.name.as_ref().and_then(|name| match &name.kind {
+        ExpressionKind::Ident(name) => Some(Value::str(name)),
+        _ => None
+    });
+    vec![
+        ("name",name.unwrap_or(Value::Null)),
+        ("params",expressions(f.param_exp.as_deref().unwrap_or_default())),
+        ("ret",Value::opt(f.ret_type.as_ref(),ty)),
+        ("body",statements(&f.body)),
+        ("lambda",Value::Bool(f.lambda)),
+        ("captures",Value::strs(&f.captures)),
+        ("doc",Value::opt(f.doc.as_deref(),Value::str)),
+    ]
+}
+fn param(param:&ParamExpression) -> Vec<(&'static str,Value)> {
+    vec![
+        ("name",Value::str(&param.name)),
+        ("type",ty(&param.token_type)),
+        ("default",Value::opt(param.default.as_ref(),expression)),
+        ("variadic",Value::Bool(param.variadic)),
+    ]
+}
+fn expression(exp:&Expression) -> Value {
+    let fields = match &exp.kind {
+        ExpressionKind::Int(val) => vec![("value",Value::Number(val.to_string()))],
+        ExpressionKind::Float(val) => vec![("value",Value::Number(float(*val)))],
+        ExpressionKind::Bool(val) => vec![("value",Value::Bool(*val))],
+        ExpressionKind::String(val) => vec![("value",Value::str(val))],
+        ExpressionKind::Ident(name) => vec![("name",Value::str(name))],
+        ExpressionKind::Infix(infix) => vec![
+            ("op",op(&infix.op_symbol)),
+            ("left",expression(&infix.left)),
+            ("right",expression(&infix.right)),
+        ],
+        ExpressionKind::Param(p) => param(p),
+        ExpressionKind::NamedArg(name,exp) => vec![("name",Value::str(name)),("value",expression(exp))],
+        ExpressionKind::Struct(fields) => vec![("fields",expressions(fields))],
+        ExpressionKind::FunType(params,ret) => vec![
+            ("params",Value::opt(params.as_deref(),expressions)),
+            ("ret",Value::opt(ret.as_ref(),ty)),
+        ],
+        ExpressionKind::Basic(tok) => vec![("type",ty(tok))],
+        ExpressionKind::ListValue(list) | ExpressionKind::Tuple(list) => vec![("items",expressions(list))],
+        ExpressionKind::Call(f,args) => vec![("callee",expression(f)),("args",expressions(args))],
+        ExpressionKind::Index(left,index) => vec![("target",expression(left)),("index",expression(index))],
+        ExpressionKind::Field(left,name) => vec![("target",expression(left)),("name",Value::str(name))],
+        ExpressionKind::SelfOp(exp,tok,prefix) => vec![
+            ("target",expression(exp)),
+            ("op",op(tok)),
+            ("prefix",Value::Bool(*prefix)),
+        ],
+        ExpressionKind::Fun(f) => fun(f),
+        ExpressionKind::Try(exp) => vec![("value",expression(exp))],
+        ExpressionKind::Nil | ExpressionKind::Unknown => vec![],
+    };
+    Value::Node(exp.kind.name(),Some(exp.span),fields)
+}
+
+fn write_str(s:&str,out:&mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}",c as u32)),
+            c => out.push(c)
+        }
+    }
+    out.push('"');
+}
+fn write_json(v:&Value,out:&mut String) {
+    match v {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(&b.to_string()),
+        Value::Number(n) => out.push_str(n),
+        Value::Str(s) => write_str(s,out),
+        Value::List(list) => {
+            out.push('[');
+            for (i,v) in list.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json(v,out);
+            }
+            out.push(']');
+        },
+        Value::Node(kind,span,fields) => {
+            out.push_str("{\"kind\":");
+            write_str(kind,out);
+            if let Some(span) = span {
+                out.push_str(&format!(",\"span\":{{\"file\":{},\"start\":{},\"end\":{},\"line\":{},\"col\":{}}}",
+                    span.file_id,span.start,span.end,span.line,span.col));
+            }
+            for (name,v) in fields {
+                out.push_str(&format!(",\"{}\":",name));
+                write_json(v,out);
+            }
+            out.push('}');
+        }
+    }
+}
+fn write_sexp(v:&Value,out:&mut String) {
+    match v {
+        Value::Null => out.push_str("nil"),
+        Value::Bool(b) => out.push_str(&b.to_string()),
+        Value::Number(n) => out.push_str(n),
+        Value::Str(s) => write_str(s,out),
+        Value::List(list) => {
+            out.push('(');
+            for (i,v) in list.iter().enumerate() {
+                if i > 0 {
+                    out.push(' ');
+                }
+                write_sexp(v,out);
+            }
+            out.push(')');
+        },
+        Value::Node(kind,_,fields) => {
+            out.push('(');
+            out.push_str(kind);
+            for (name,v) in fields.iter().filter(|(_,v)| !v.is_empty()) {
+                out.push_str(&format!(" :{} ",name));
+                write_sexp(v,out);
+            }
+            out.push(')');
+        }
+    }
+}
+
+//a small reader for the json written above, enough to check it is well formed
+#[cfg(test)]
+fn read_json(s:&[u8],i:&mut usize) -> Result<(),String> {
+    fn ws(s:&[u8],i:&mut usize) {
+        while *i < s.len() && s[*i].is_ascii_whitespace() {
+            *i += 1;
+        }
+    }
+    ws(s,i);
+    match s.get(*i) {
+        Some(b'{') | Some(b'[') => {
+            let close = if s[*i] == b'{' { b'}' } else { b']' };
+            let object = close == b'}';
+            *i += 1;
+            ws(s,i);
+            if s.get(*i) == Some(&close) {
+                *i += 1;
+                return Ok(())
+            }
+            loop {
+                if object {
+                    read_json(s,i)?;
+                    ws(s,i);
+                    if s.get(*i) != Some(&b':') {
+                        return Err(format!("':' expected at {}",i))
+                    }
+                    *i += 1;
+                }
+                read_json(s,i)?;
+                ws(s,i);
+                match s.get(*i) {
+                    Some(b',') => *i += 1,
+                    Some(c) if *c == close => {
+                        *i += 1;
+                        return Ok(())
+                    },
+                    _ => return Err(format!("',' expected at {}",i))
+                }
+            }
+        },
+        Some(b'"') => {
+            *i += 1;
+            while *i < s.len() && s[*i] != b'"' {
+                if s[*i] == b'\\' {
+                    *i += 1;
+                }
+                if s[*i] < 0x20 {
+                    return Err(format!("raw control character at {}",i))
+                }
+                *i += 1;
+            }
+            *i += 1;
+            Ok(())
+        },
+        Some(_) => {
+            let start = *i;
+            while *i < s.len() && !b",]}: \n".contains(&s[*i]) {
+                *i += 1;
+            }
+            let word = std::str::from_utf8(&s[start..*i]).unwrap();
+            if matches!(word,"null" | "true" | "false") || word.parse::<f64>().is_ok() {
+                return Ok(())
+            }
+            Err(format!("bad value '{}' at {}",word,start))
+        },
+        None => Err("unexpected end".to_string())
+    }
+}
+#[test]
+fn test_export_json(){
+    let program = crate::parse_str("let a:int = b + 1\nfun f(s:string) ?int {\n    return nil\n}\nlet = 2\n");
+    let out = json(&program.statements,&program.diagnostics);
+    assert!(out.starts_with("{\"kind\":\"Program\",\"body\":[{\"kind\":\"Let\",\"span\":{\"file\":0,\"start\":0,\"end\":17,\"line\":1,\"col\":1},\"name\":\"a\""));
+    assert!(out.contains("\"kind\":\"Infix\",\"span\":{\"file\":0,\"start\":12,\"end\":17,\"line\":1,\"col\":13},\"op\":\"+\""));
+    assert!(out.contains("\"params\":[{\"kind\":\"Param\",\"span\":{\"file\":0,\"start\":24,\"end\":32,\"line\":2,\"col\":7},\"name\":\"s\",\"type\":\"string\",\"default\":null,\"variadic\":false}],\"ret\":\"?int\""));
+    assert!(out.contains("{\"kind\":\"Error\",\"span\""));
+    assert!(out.contains("\"diagnostics\":[{\"kind\":\"Diagnostic\",\"span\":{\"file\":0,\"start\":58,\"end\":61,\"line\":5,\"col\":1},\"severity\":\"error\",\"message\":\"expected name is missing\"}"));
+
+    let escaped = crate::parse_str("let s:string = \"a\\b\tc\"\n");
+    assert!(json(&escaped.statements,&[]).contains("\"value\":\"a\\\\b\\tc\""));
+
+    let mut paths:Vec<_> = std::fs::read_dir("./src/script").unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "kz"))
+        .collect();
+    paths.sort();
+    for path in paths {
+        let program = crate::parse_file(path.to_str().unwrap()).unwrap();
+        let out = json(&program.statements,&program.diagnostics);
+        let mut i = 0;
+        read_json(out.as_bytes(),&mut i).unwrap_or_else(|e| panic!("{}: {}",path.display(),e));
+        assert_eq!(i,out.len(),"{}",path.display());
+    }
+}
+#[test]
+fn test_export_sexp(){
+    let program = crate::parse_str("let a:int = b + 1\nif let x = m {\n    f(x, level: 2)?\n}\nfor let i:int = 0;i < 3;i++ {\n    break\n}\n");
+    let out = sexp(&program.statements);
+    let lines:Vec<&str> = out.lines().collect();
+    assert_eq!(lines,[
+        "(Let :name \"a\" :type \"int\" :value (Infix :op \"+\" :left (Ident :name \"b\") :right (Int :value 1)))",
+        "(If :binding \"x\" :condition (Ident :name \"m\") :then ((Invoke :value (Try :value (Call :callee (Ident :name \"f\") :args ((Ident :name \"x\") (NamedArg :name \"level\" :value (Int :value 2))))))))",
+        "(For :init (Let :name \"i\" :type \"int\" :value (Int :value 0)) :condition (Infix :op \"<\" :left (Ident :name \"i\") :right (Int :value 3)) :step (Invoke :value (SelfOp :target (Ident :name \"i\") :op \"++\")) :body ((Break)))",
+    ]);
+}
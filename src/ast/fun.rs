@@ -1,13 +1,19 @@
-use crate::ast::node::{Expression, Statement};
+use crate::ast::node::{Expression, ExpressionKind, Statement, StatementKind};
 use crate::parser::token::Token;
 
-#[derive(Debug,PartialEq)]
+#[derive(Debug,PartialEq,Default)]
 pub struct FunExpression {
     pub name:Option<Expression>,
     pub ret_type:Option<Token>,
     pub param_number:usize,
     pub param_exp:Option<Vec<Expression>>,
-    pub body:Vec<Statement>
+    pub body:Vec<Statement>,
+    //|x| x * 2  (x) => x * 2
+    pub lambda:bool,
+    //outer variables used by the body, filled in by analysis::capture
+    pub captures:Vec<String>,
+    //  /// text above a named function
+    pub doc:Option<String>,
 }
 
 impl FunExpression {
@@ -18,6 +24,46 @@ impl FunExpression {
             param_number:0,
             param_exp:None,
             body:vec![],
+            lambda:false,
+            captures:vec![],
+            doc:None,
         }
     }
-}
\ No newline at end of file
+    //return type of a lambda taken from its first return,
+    //Token::Unknown when it is left to type inference
+    pub fn infer_ret_type(&self) -> Option<Token> {
+        self.body.iter().find_map(|stem| match &stem.kind {
+            StatementKind::Return(Some(exp)) => Some(self.infer_type(exp)),
+            _ => None
+        })
+    }
+    fn infer_type(&self,exp:&Expression) -> Token {
+        match &exp.kind {
+            ExpressionKind::Int(_) => Token::Int,
+            ExpressionKind::Float(_) => Token::Float,
+            ExpressionKind::String(_) => Token::String,
+            ExpressionKind::Bool(_) => Token::Bool,
+            ExpressionKind::Tuple(list) => Token::Tuple(list.iter().map(|exp| self.infer_type(exp)).collect()),
+            ExpressionKind::Ident(name) => {
+                self.param_exp.iter().flatten().find_map(|param| match &param.kind {
+                    ExpressionKind::Param(param) if param.name == *name => Some(param.token_type.clone()),
+                    _ => None
+                }).unwrap_or(Token::Unknown)
+            },
+            ExpressionKind::Infix(infix) => match infix.op_symbol {
+                Token::LT | Token::GT | Token::LTEqu | Token::GTEqu | Token::Equ | Token::BangEqu
+                | Token::And | Token::Or => Token::Bool,
+                _ => {
+                    let left = self.infer_type(&infix.left);
+                    let right = self.infer_type(&infix.right);
+                    match (left,right) {
+                        (Token::Float,Token::Int) | (Token::Int,Token::Float) => Token::Float,
+                        (left,right) if left == right => left,
+                        _ => Token::Unknown
+                    }
+                }
+            },
+            _ => Token::Unknown
+        }
+    }
+}
@@ -2,6 +2,8 @@ use crate::ast::node::{Expression, Statement};
 
 #[derive(Debug,PartialEq)]
 pub struct IfStatement {
+    //if let x = maybe {}
+    pub binding:Option<String>,
     pub condition:Expression,
     pub consequence:Vec<Statement>,
     pub alternative:Vec<Statement>,
@@ -10,6 +12,7 @@ pub struct IfStatement {
 impl IfStatement {
     pub fn new(condition:Expression) -> IfStatement {
         Self {
+            binding:None,
             condition,
             consequence:Vec::new(),
             alternative:Vec::new(),
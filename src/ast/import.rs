@@ -0,0 +1,25 @@
+#[derive(Debug,PartialEq)]
+pub struct ImportStatement {
+    //"lib/math.kz" or lib.math
+    pub path:String,
+    pub module:bool,
+    //import math.{sqrt,pow}
+    pub names:Vec<String>,
+}
+
+impl ImportStatement {
+    pub fn new(path:String,module:bool) -> ImportStatement {
+        Self {
+            path,
+            module,
+            names:vec![],
+        }
+    }
+    //file path relative to the importing file
+    pub fn file(&self) -> String {
+        if self.module {
+            return format!("{}.kz",self.path.replace('.',"/"))
+        }
+        self.path.clone()
+    }
+}
@@ -1,4 +1,4 @@
-use crate::ast::node::Expression;
+use crate::ast::node::{Expression, ExpressionKind};
 use crate::parser::token::Token;
 
 #[derive(Debug,PartialEq)]
@@ -12,7 +12,7 @@ impl InfixExpression {
     pub fn new(left:Expression) -> InfixExpression {
         Self {
             left,
-            right:Expression::Unknown,
+            right:ExpressionKind::Unknown.into(),
             op_symbol:Token::Unknown,
         }
     }
@@ -6,14 +6,41 @@ use crate::parser::token::Token;
 pub struct LetStatement {
      pub token_type:Token,
      pub ident:String,
+     pub tuple:Vec<String>,
      pub exp:Option<Expression>,
+     //  /// text above the let or const
+     pub doc:Option<String>,
 }
 impl LetStatement {
     pub fn new(ident:String) -> LetStatement {
         Self {
             token_type: Token::Unknown,
             ident,
-            exp: None
+            tuple: vec![],
+            exp: None,
+            doc: None
         }
     }
+    //let (a,b) = ...
+    pub fn new_tuple(names:Vec<String>) -> LetStatement {
+        Self {
+            token_type: Token::Unknown,
+            ident: String::new(),
+            tuple: names,
+            exp: None,
+            doc: None
+        }
+    }
+    pub fn is_tuple(&self) -> bool {
+        !self.tuple.is_empty()
+    }
+    pub fn is_optional(&self) -> bool {
+        matches!(self.token_type,Token::Optional(_))
+    }
+    pub fn names(&self) -> Vec<&str> {
+        if self.is_tuple() {
+            return self.tuple.iter().map(|n| n.as_str()).collect()
+        }
+        vec![self.ident.as_str()]
+    }
 }
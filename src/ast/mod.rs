@@ -3,4 +3,10 @@ pub mod lets;
 pub mod infix;
 pub mod ifs;
 pub mod fun;
-pub mod for_;
\ No newline at end of file
+pub mod for_;
+pub mod import;
+pub mod param;
+pub mod type_;
+pub mod visit;
+pub mod printer;
+pub mod export;
\ No newline at end of file
@@ -1,38 +1,162 @@
 use crate::ast::for_::ForStatement;
 use crate::ast::fun::{FunExpression};
 use crate::ast::ifs::IfStatement;
+use crate::ast::import::ImportStatement;
 use crate::ast::infix::InfixExpression;
 use crate::ast::lets::LetStatement;
+use crate::ast::param::ParamExpression;
+use crate::ast::type_::TypeStatement;
+use crate::parser::span::Span;
 use crate::parser::token::Token;
 
+//a statement and where it was parsed from, spans never take part in ==
+#[derive(Debug)]
+pub struct Statement {
+    pub kind:StatementKind,
+    pub span:Span,
+}
+impl PartialEq for Statement {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+impl From<StatementKind> for Statement {
+    fn from(kind: StatementKind) -> Self {
+        Statement::new(kind,Span::default())
+    }
+}
 #[derive(Debug,PartialEq)]
-pub enum  Statement {
+pub enum StatementKind {
     Let(LetStatement),
+    Const(LetStatement),
+    Assign(Expression,Expression),
     IF(IfStatement),
     Fun(FunExpression),
-    Type(String,Expression),
+    Type(TypeStatement),
     Invoke(Expression),
     For(ForStatement),
-    Return(Expression),
+    //return  return exp
+    Return(Option<Expression>),
     Break,
     Continue,
+    Import(ImportStatement),
+    Pub(Box<Statement>),
+    //defer close(f)  defer { ... }
+    Defer(Vec<Statement>),
+    //a statement that failed to parse, see Parser::program_recover,
+    //its span covers the tokens skipped by recovery
+    Error,
+}
+impl StatementKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatementKind::Let(_) => "Let",
+            StatementKind::Const(_) => "Const",
+            StatementKind::Assign(_,_) => "Assign",
+            StatementKind::IF(_) => "If",
+            StatementKind::Fun(_) => "Fun",
+            StatementKind::Type(_) => "Type",
+            StatementKind::Invoke(_) => "Invoke",
+            StatementKind::For(_) => "For",
+            StatementKind::Return(_) => "Return",
+            StatementKind::Break => "Break",
+            StatementKind::Continue => "Continue",
+            StatementKind::Import(_) => "Import",
+            StatementKind::Pub(_) => "Pub",
+            StatementKind::Defer(_) => "Defer",
+            StatementKind::Error => "Error",
+        }
+    }
+}
+impl Statement {
+    pub fn new(kind:StatementKind,span:Span) -> Statement {
+        Self {
+            kind,
+            span
+        }
+    }
+    //defer bodies of a scope in the order they run on exit, last declared first
+    pub fn deferred(body:&[Statement]) -> Vec<&[Statement]> {
+        body.iter().rev().filter_map(|stem| match &stem.kind {
+            StatementKind::Defer(stems) => Some(stems.as_slice()),
+            _ => None
+        }).collect()
+    }
+}
+//an expression and where it was parsed from, spans never take part in ==
+#[derive(Debug)]
+pub struct Expression {
+    pub kind:ExpressionKind,
+    pub span:Span,
+}
+impl PartialEq for Expression {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+impl From<ExpressionKind> for Expression {
+    fn from(kind: ExpressionKind) -> Self {
+        Expression::new(kind,Span::default())
+    }
+}
+impl Expression {
+    pub fn new(kind:ExpressionKind,span:Span) -> Expression {
+        Self {
+            kind,
+            span
+        }
+    }
 }
 #[derive(Debug,PartialEq)]
-pub enum Expression {
+pub enum ExpressionKind {
     Int(i32),
     String(String),
     Float(f32),
     Bool(bool),
     Infix(Box<InfixExpression>),
     Ident(String),
-    Param(String,Token),
-    Struct(Box<Vec<Expression>>),
-    FunType(Option<Box<Vec<Expression>>>,Option<Token>),
+    Param(Box<ParamExpression>),
+    NamedArg(String,Box<Expression>),
+    Struct(Vec<Expression>),
+    FunType(Option<Vec<Expression>>,Option<Token>),
     Basic(Token),
-    ListValue(Vec<Box<Expression>>),
-    Call(Box<Expression>,Vec<Box<Expression>>),
+    ListValue(Vec<Expression>),
+    Tuple(Vec<Expression>),
+    Call(Box<Expression>,Vec<Expression>),
     Index(Box<Expression>,Box<Expression>),
+    //user.age
+    Field(Box<Expression>,String),
     SelfOp(Box<Expression>,Token,bool),
     Fun(Box<FunExpression>),
+    //f()? returns early when the value carries an error
+    Try(Box<Expression>),
+    Nil,
     Unknown
-}
\ No newline at end of file
+}
+impl ExpressionKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExpressionKind::Int(_) => "Int",
+            ExpressionKind::String(_) => "String",
+            ExpressionKind::Float(_) => "Float",
+            ExpressionKind::Bool(_) => "Bool",
+            ExpressionKind::Infix(_) => "Infix",
+            ExpressionKind::Ident(_) => "Ident",
+            ExpressionKind::Param(_) => "Param",
+            ExpressionKind::NamedArg(_,_) => "NamedArg",
+            ExpressionKind::Struct(_) => "Struct",
+            ExpressionKind::FunType(_,_) => "FunType",
+            ExpressionKind::Basic(_) => "Basic",
+            ExpressionKind::ListValue(_) => "ListValue",
+            ExpressionKind::Tuple(_) => "Tuple",
+            ExpressionKind::Call(_,_) => "Call",
+            ExpressionKind::Index(_,_) => "Index",
+            ExpressionKind::Field(_,_) => "Field",
+            ExpressionKind::SelfOp(_,_,_) => "SelfOp",
+            ExpressionKind::Fun(_) => "Fun",
+            ExpressionKind::Try(_) => "Try",
+            ExpressionKind::Nil => "Nil",
+            ExpressionKind::Unknown => "Unknown",
+        }
+    }
+}
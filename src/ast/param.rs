@@ -0,0 +1,23 @@
+use crate::ast::node::Expression;
+use crate::parser::token::Token;
+
+#[derive(Debug,PartialEq)]
+pub struct ParamExpression {
+    pub name:String,
+    pub token_type:Token,
+    //level:int = 1
+    pub default:Option<Expression>,
+    //...args:[]string
+    pub variadic:bool,
+}
+
+impl ParamExpression {
+    pub fn new(name:String,token_type:Token) -> ParamExpression {
+        Self {
+            name,
+            token_type,
+            default:None,
+            variadic:false,
+        }
+    }
+}
@@ -0,0 +1,452 @@
+use std::fmt::{Display, Formatter};
+use crate::ast::fun::FunExpression;
+use crate::ast::ifs::IfStatement;
+use crate::ast::lets::LetStatement;
+use crate::ast::node::{Expression, ExpressionKind, Statement, StatementKind};
+use crate::ast::param::ParamExpression;
+use crate::parser::token::{Operation, Token};
+
+//canonical kz source for a tree, parsing it again gives back an equal tree.
+//layout is fixed: four spaces per block, one statement per line, no blank lines
+pub fn program(stems:&[Statement]) -> String {
+    let mut p = Printer::default();
+    p.block(stems);
+    p.out
+}
+//int  []?string  (int,error)
+pub fn type_name(tok:&Token) -> String {
+    match tok {
+        Token::Int => "int".to_string(),
+        Token::String => "string".to_string(),
+        Token::Float => "float".to_string(),
+        Token::Bool => "bool".to_string(),
+        Token::Void => "void".to_string(),
+        Token::Ident(name) => name.clone(),
+        Token::Array(0,tok) => format!("[]{}",type_name(tok)),
+        Token::Array(number,tok) => format!("[{}]{}",number,type_name(tok)),
+        Token::Tuple(toks) => format!("({})",toks.iter().map(type_name).collect::<Vec<String>>().join(",")),
+        Token::Optional(tok) => format!("?{}",type_name(tok)),
+        _ => format!("{:?}",tok)
+    }
+}
+
+//always with a point and never with an exponent, so it lexes back as the same float.
+//the parser never gives an infinite float, see Parser::basics
+pub fn float(val:f32) -> String {
+    let text = val.to_string();
+    if text.contains('.') { text } else { format!("{}.0",text) }
+}
+
+impl Display for Statement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut p = Printer::default();
+        p.statement(self);
+        write!(f,"{}",p.out.trim_end_matches('\n'))
+    }
+}
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut p = Printer::default();
+        p.expression(self);
+        write!(f,"{}",p.out)
+    }
+}
+
+#[derive(Default)]
+struct Printer {
+    out:String,
+    indent:usize,
+}
+
+impl Printer {
+    fn push(&mut self,s:&str) {
+        self.out.push_str(s)
+    }
+    fn line(&mut self) {
+        for _ in 0..self.indent {
+            self.push("    ");
+        }
+    }
+    fn block(&mut self,stems:&[Statement]) {
+        for stem in stems {
+            self.statement(stem);
+        }
+    }
+    //{ stems }, the caller has printed everything up to the brace
+    fn body(&mut self,stems:&[Statement]) {
+        self.push("{\n");
+        self.indent += 1;
+        self.block(stems);
+        self.indent -= 1;
+        self.line();
+        self.push("}");
+    }
+    fn doc(&mut self,doc:&Option<String>) {
+        for text in doc.iter().flat_map(|doc| doc.split('\n')) {
+            self.line();
+            if text.is_empty() {
+                self.push("///\n");
+            } else {
+                self.push(&format!("/// {}\n",text));
+            }
+        }
+    }
+    //a whole line, doc notes of a let, fun or type go above it
+    fn statement(&mut self,stem:&Statement) {
+        //a statement that failed to parse has nothing left to print
+        if stem.kind == StatementKind::Error {
+            return
+        }
+        let mut inner = stem;
+        if let StatementKind::Pub(stem) = &stem.kind {
+            inner = stem;
+        }
+        match &inner.kind {
+            StatementKind::Let(l) | StatementKind::Const(l) => self.doc(&l.doc),
+            StatementKind::Fun(f) => self.doc(&f.doc),
+            StatementKind::Type(t) => self.doc(&t.doc),
+            _ => {}
+        }
+        self.line();
+        self.inline(stem);
+        self.push("\n");
+    }
+    //a statement without its indent and newline, as in the head of a for
+    fn inline(&mut self,stem:&Statement) {
+        match &stem.kind {
+            StatementKind::Let(l) => self.let_(l,"let"),
+            StatementKind::Const(l) => self.let_(l,"const"),
+            StatementKind::Assign(target,exp) => {
+                self.expression(target);
+                self.push(" = ");
+                self.expression(exp);
+            },
+            StatementKind::IF(i) => self.if_(i),
+            StatementKind::Fun(f) => self.fun(f),
+            StatementKind::Type(t) => {
+                self.push(&format!("type {} ",t.name));
+                match &t.exp.kind {
+                    ExpressionKind::Struct(fields) => {
+                        self.push("struct {\n");
+                        self.indent += 1;
+                        for field in fields {
+                            self.line();
+                            self.expression(field);
+                            self.push("\n");
+                        }
+                        self.indent -= 1;
+                        self.line();
+                        self.push("}");
+                    },
+                    _ => self.expression(&t.exp)
+                }
+            },
+            StatementKind::Invoke(exp) => self.expression(exp),
+            StatementKind::For(f) => {
+                self.push("for ");
+                self.inline(&f.start_condition);
+                self.push("; ");
+                self.expression(&f.condition);
+                self.push("; ");
+                self.inline(&f.self_operation);
+                self.push(" ");
+                self.body(&f.consequence);
+            },
+            StatementKind::Return(Some(exp)) => {
+                self.push("return ");
+                self.expression(exp);
+            },
+            StatementKind::Return(None) => self.push("return"),
+            StatementKind::Break => self.push("break"),
+            StatementKind::Continue => self.push("continue"),
+            StatementKind::Import(import) => {
+                if !import.module {
+                    self.push(&format!("import \"{}\"",import.path));
+                } else if import.names.is_empty() {
+                    self.push(&format!("import {}",import.path));
+                } else {
+                    self.push(&format!("import {}.{{{}}}",import.path,import.names.join(", ")));
+                }
+            },
+            StatementKind::Pub(stem) => {
+                self.push("pub ");
+                self.inline(stem);
+            },
+            StatementKind::Defer(stems) => {
+                self.push("defer ");
+                match stems.as_slice() {
+                    [Statement { kind:StatementKind::Invoke(exp), .. }] => self.expression(exp),
+                    _ => self.body(stems)
+                }
+            },
+            StatementKind::Error => {}
+        }
+    }
+    fn let_(&mut self,l:&LetStatement,keyword:&str) {
+        self.push(keyword);
+        self.push(" ");
+        if l.is_tuple() {
+            self.push(&format!("({})",l.tuple.join(", ")));
+        } else {
+            self.push(&l.ident);
+        }
+        if l.token_type != Token::Unknown {
+            self.push(&format!(":{}",type_name(&l.token_type)));
+        }
+        if let Some(exp) = &l.exp {
+            self.push(" = ");
+            self.expression(exp);
+        }
+    }
+    //else { if .. } and else if .. parse to the same tree, the latter is printed
+    fn if_(&mut self,i:&IfStatement) {
+        self.push("if ");
+        if let Some(name) = &i.binding {
+            self.push(&format!("let {} = ",name));
+        }
+        self.expression(&i.condition);
+        self.push(" ");
+        self.body(&i.consequence);
+        match i.alternative.as_slice() {
+            [] => {},
+            [Statement { kind:StatementKind::IF(i), .. }] => {
+                self.push(" else ");
+                self.if_(i);
+            },
+            stems => {
+                self.push(" else ");
+                self.body(stems);
+            }
+        }
+    }
+    fn fun(&mut self,f:&FunExpression) {
+        if f.lambda {
+            return self.lambda(f)
+        }
+        self.push("fun");
+        if let Some(name) = &f.name {
+            self.push(" ");
+            self.expression(name);
+        }
+        self.params(f.param_exp.as_deref().unwrap_or_default());
+        if let Some(tok) = &f.ret_type {
+            self.push(&format!(" -> {}",type_name(tok)));
+        }
+        self.push(" ");
+        self.body(&f.body);
+    }
+    //|x| x * 2, the return type is inferred again on parsing
+    fn lambda(&mut self,f:&FunExpression) {
+        let params = f.param_exp.as_deref().unwrap_or_default();
+        let params:Vec<String> = params.iter().map(|exp| match &exp.kind {
+            ExpressionKind::Param(param) if param.token_type == Token::Unknown => param.name.clone(),
+            _ => exp.to_string()
+        }).collect();
+        self.push(&format!("|{}| ",params.join(", ")));
+        match f.body.as_slice() {
+            [Statement { kind:StatementKind::Return(Some(exp)), .. }] => self.expression(exp),
+            stems => self.body(stems)
+        }
+    }
+    fn params(&mut self,params:&[Expression]) {
+        self.push("(");
+        self.list(params);
+        self.push(")");
+    }
+    fn list(&mut self,list:&[Expression]) {
+        for (i,exp) in list.iter().enumerate() {
+            if i > 0 {
+                self.push(", ");
+            }
+            self.expression(exp);
+        }
+    }
+    fn param(&mut self,param:&ParamExpression) {
+        if param.variadic {
+            self.push("...");
+        }
+        if !param.name.is_empty() {
+            self.push(&param.name);
+            self.push(":");
+        }
+        self.push(&type_name(&param.token_type));
+        if let Some(exp) = &param.default {
+            self.push(" = ");
+            self.expression(exp);
+        }
+    }
+    fn expression(&mut self,exp:&Expression) {
+        match &exp.kind {
+            ExpressionKind::Int(val) => self.push(&val.to_string()),
+            ExpressionKind::Float(val) => self.push(&float(*val)),
+            ExpressionKind::Bool(val) => self.push(&val.to_string()),
+            ExpressionKind::String(val) => self.push(&format!("\"{}\"",val)),
+            ExpressionKind::Ident(name) => self.push(name),
+            ExpressionKind::Nil => self.push("nil"),
+            ExpressionKind::Infix(infix) => {
+                let op = precedence(exp);
+                self.operand(&infix.left,precedence(&infix.left) < op);
+                self.push(&format!(" {:?} ",infix.op_symbol));
+                //operators of one level group to the left
+                self.operand(&infix.right,precedence(&infix.right) <= op);
+            },
+            ExpressionKind::Param(param) => self.param(param),
+            ExpressionKind::NamedArg(name,exp) => {
+                self.push(&format!("{}: ",name));
+                self.expression(exp);
+            },
+            ExpressionKind::Struct(fields) => {
+                self.push("struct {");
+                self.list(fields);
+                self.push("}");
+            },
+            ExpressionKind::FunType(params,ret) => {
+                self.push("fun");
+                self.params(params.as_deref().unwrap_or_default());
+                if let Some(tok) = ret {
+                    self.push(&format!(" -> {}",type_name(tok)));
+                }
+            },
+            ExpressionKind::Basic(tok) => self.push(&type_name(tok)),
+            ExpressionKind::ListValue(list) => {
+                self.push("[");
+                self.list(list);
+                self.push("]");
+            },
+            ExpressionKind::Tuple(list) => {
+                self.push("(");
+                self.list(list);
+                self.push(")");
+            },
+            ExpressionKind::Call(f,args) => {
+                self.postfix(f);
+                self.params(args);
+            },
+            ExpressionKind::Index(left,index) => {
+                self.postfix(left);
+                self.push("[");
+                self.expression(index);
+                self.push("]");
+            },
+            ExpressionKind::Field(left,name) => {
+                self.postfix(left);
+                self.push(&format!(".{}",name));
+            },
+            ExpressionKind::SelfOp(exp,tok,true) => {
+                self.push(&format!("{:?}",tok));
+                self.expression(exp);
+            },
+            ExpressionKind::SelfOp(exp,tok,false) => {
+                self.postfix(exp);
+                self.push(&format!("{:?}",tok));
+            },
+            ExpressionKind::Try(exp) => {
+                self.postfix(exp);
+                self.push("?");
+            },
+            ExpressionKind::Fun(f) => self.fun(f),
+            ExpressionKind::Unknown => {}
+        }
+    }
+    fn operand(&mut self,exp:&Expression,group:bool) {
+        if group {
+            self.push("(");
+            self.expression(exp);
+            self.push(")");
+        } else {
+            self.expression(exp);
+        }
+    }
+    //the left side of a call, index, field, ? or ++
+    fn postfix(&mut self,exp:&Expression) {
+        self.operand(exp,precedence(exp) < Operation::Index)
+    }
+}
+
+//how tightly an expression holds together when printed next to an operator,
+//a lambda body runs to the end of the line so it is grouped everywhere
+fn precedence(exp:&Expression) -> Operation {
+    match &exp.kind {
+        ExpressionKind::Infix(infix) => match infix.op_symbol {
+            Token::Or => Operation::Or,
+            Token::And => Operation::And,
+            Token::Equ | Token::BangEqu => Operation::EquAls,
+            Token::LT | Token::GT | Token::LTEqu | Token::GTEqu => Operation::LtAndGt,
+            Token::Add | Token::Sub => Operation::AddAndSub,
+            _ => Operation::MulAndDiv
+        },
+        ExpressionKind::SelfOp(_,_,_) => Operation::AddAndSub,
+        ExpressionKind::Fun(f) if f.lambda => Operation::Lowest,
+        _ => Operation::Call
+    }
+}
+
+#[cfg(test)]
+fn without_errors(stems:&mut Vec<Statement>) {
+    use crate::ast::visit::{MutVisitor, walk_statement_mut, walk_expression_mut};
+    //statements that failed to parse are not printed, drop them at every depth
+    struct Strip;
+    impl MutVisitor for Strip {
+        fn visit_statement(&mut self,stem:&mut Statement) {
+            match &mut stem.kind {
+                StatementKind::IF(i) => {
+                    i.consequence.retain(|s| s.kind != StatementKind::Error);
+                    i.alternative.retain(|s| s.kind != StatementKind::Error);
+                },
+                StatementKind::For(f) => f.consequence.retain(|s| s.kind != StatementKind::Error),
+                StatementKind::Defer(stems) => stems.retain(|s| s.kind != StatementKind::Error),
+                StatementKind::Fun(f) => f.body.retain(|s| s.kind != StatementKind::Error),
+                _ => {}
+            }
+            walk_statement_mut(self,stem)
+        }
+        fn visit_expression(&mut self,exp:&mut Expression) {
+            if let ExpressionKind::Fun(f) = &mut exp.kind {
+                f.body.retain(|s| s.kind != StatementKind::Error);
+            }
+            walk_expression_mut(self,exp)
+        }
+    }
+    stems.retain(|s| s.kind != StatementKind::Error);
+    crate::ast::visit::walk_program_mut(&mut Strip,stems);
+}
+#[test]
+fn test_printer_round_trip(){
+    let mut paths:Vec<_> = std::fs::read_dir("./src/script").unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "kz"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+    for path in paths {
+        let mut original = crate::parse_file(path.to_str().unwrap()).unwrap().statements;
+        let source = program(&original);
+        let printed = crate::parse_str(&source).statements;
+        without_errors(&mut original);
+        assert!(printed == original,"{} printed as\n{}",path.display(),source);
+        //printing is a fixed point
+        assert_eq!(program(&printed),source,"{}",path.display());
+    }
+}
+#[test]
+fn test_printer_expression(){
+    let cases = [
+        ("(a + b) * c","(a + b) * c"),
+        ("a - (b - c)","a - (b - c)"),
+        ("a - b - c","a - b - c"),
+        ("(a || b) && c.d == 1","(a || b) && c.d == 1"),
+        ("user.age > 18 && region == \"cn\"","user.age > 18 && region == \"cn\""),
+        ("(a + b)[0].c(1, level: 2)?","(a + b)[0].c(1, level: 2)?"),
+        ("(|x| x)(1) + 2.50","(|x| x)(1) + 2.5"),
+        ("100000000000000000000.0 + 0.000001","100000000000000000000.0 + 0.000001"),
+        ("(x:int, y) => x < y","|x:int, y| x < y"),
+        ("[1,(2,\"a\"),nil]","[1, (2, \"a\"), nil]"),
+    ];
+    for (source,expected) in cases {
+        let exp = crate::Parser::parse_expression(source).unwrap();
+        assert_eq!(exp.to_string(),expected);
+        assert_eq!(crate::Parser::parse_expression(expected).unwrap(),exp);
+    }
+    assert_eq!(type_name(&crate::Parser::parse_type("[]?(int,error)").unwrap()),"[]?(int,error)");
+    assert_eq!(type_name(&crate::Parser::parse_type("[3]point").unwrap()),"[3]point");
+}
@@ -0,0 +1,20 @@
+use crate::ast::node::Expression;
+
+#[derive(Debug,PartialEq)]
+pub struct TypeStatement {
+    pub name:String,
+    //struct {...}, fun(...) or a basic type
+    pub exp:Expression,
+    //  /// text above the type
+    pub doc:Option<String>,
+}
+
+impl TypeStatement {
+    pub fn new(name:String,exp:Expression) -> TypeStatement {
+        Self {
+            name,
+            exp,
+            doc:None,
+        }
+    }
+}
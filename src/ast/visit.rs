@@ -0,0 +1,359 @@
+use crate::ast::for_::ForStatement;
+use crate::ast::fun::FunExpression;
+use crate::ast::ifs::IfStatement;
+use crate::ast::import::ImportStatement;
+use crate::ast::infix::InfixExpression;
+use crate::ast::lets::LetStatement;
+use crate::ast::node::{Expression, ExpressionKind, Statement, StatementKind};
+use crate::ast::param::ParamExpression;
+use crate::ast::type_::TypeStatement;
+
+//read-only traversal, every method defaults to the matching walk_* function,
+//an override that still wants the children calls walk_* itself
+//'a is the tree's lifetime, so a visitor may keep the nodes it is handed
+pub trait Visitor<'a> {
+    fn visit_statement(&mut self,stem:&'a Statement) {
+        walk_statement(self,stem)
+    }
+    fn visit_expression(&mut self,exp:&'a Expression) {
+        walk_expression(self,exp)
+    }
+    fn visit_let(&mut self,l:&'a LetStatement) {
+        walk_let(self,l)
+    }
+    fn visit_if(&mut self,i:&'a IfStatement) {
+        walk_if(self,i)
+    }
+    fn visit_for(&mut self,f:&'a ForStatement) {
+        walk_for(self,f)
+    }
+    fn visit_fun(&mut self,f:&'a FunExpression) {
+        walk_fun(self,f)
+    }
+    fn visit_type(&mut self,t:&'a TypeStatement) {
+        walk_type(self,t)
+    }
+    fn visit_import(&mut self,_:&'a ImportStatement) {}
+    fn visit_infix(&mut self,infix:&'a InfixExpression) {
+        walk_infix(self,infix)
+    }
+    fn visit_param(&mut self,param:&'a ParamExpression) {
+        walk_param(self,param)
+    }
+}
+
+pub fn walk_program<'a,V:Visitor<'a> + ?Sized>(v:&mut V,stems:&'a [Statement]) {
+    for stem in stems {
+        v.visit_statement(stem);
+    }
+}
+pub fn walk_statement<'a,V:Visitor<'a> + ?Sized>(v:&mut V,stem:&'a Statement) {
+    match &stem.kind {
+        StatementKind::Let(l) | StatementKind::Const(l) => v.visit_let(l),
+        StatementKind::Assign(target,exp) => {
+            v.visit_expression(target);
+            v.visit_expression(exp);
+        },
+        StatementKind::IF(i) => v.visit_if(i),
+        StatementKind::Fun(f) => v.visit_fun(f),
+        StatementKind::Type(t) => v.visit_type(t),
+        StatementKind::Invoke(exp) | StatementKind::Return(Some(exp)) => v.visit_expression(exp),
+        StatementKind::For(f) => v.visit_for(f),
+        StatementKind::Import(import) => v.visit_import(import),
+        StatementKind::Pub(stem) => v.visit_statement(stem),
+        StatementKind::Defer(stems) => walk_program(v,stems),
+        StatementKind::Return(None) | StatementKind::Break | StatementKind::Continue | StatementKind::Error => {}
+    }
+}
+pub fn walk_expression<'a,V:Visitor<'a> + ?Sized>(v:&mut V,exp:&'a Expression) {
+    match &exp.kind {
+        ExpressionKind::Infix(infix) => v.visit_infix(infix),
+        ExpressionKind::Param(param) => v.visit_param(param),
+        ExpressionKind::NamedArg(_,exp) | ExpressionKind::SelfOp(exp,_,_) | ExpressionKind::Try(exp)
+        | ExpressionKind::Field(exp,_) => v.visit_expression(exp),
+        ExpressionKind::Struct(list) | ExpressionKind::ListValue(list) | ExpressionKind::Tuple(list) => {
+            for exp in list {
+                v.visit_expression(exp);
+            }
+        },
+        ExpressionKind::FunType(params,_) => {
+            for exp in params.iter().flatten() {
+                v.visit_expression(exp);
+            }
+        },
+        ExpressionKind::Call(f,args) => {
+            v.visit_expression(f);
+            for exp in args {
+                v.visit_expression(exp);
+            }
+        },
+        ExpressionKind::Index(left,index) => {
+            v.visit_expression(left);
+            v.visit_expression(index);
+        },
+        ExpressionKind::Fun(f) => v.visit_fun(f),
+        ExpressionKind::Int(_) | ExpressionKind::String(_) | ExpressionKind::Float(_) | ExpressionKind::Bool(_)
+        | ExpressionKind::Ident(_) | ExpressionKind::Basic(_) | ExpressionKind::Nil | ExpressionKind::Unknown => {}
+    }
+}
+pub fn walk_let<'a,V:Visitor<'a> + ?Sized>(v:&mut V,l:&'a LetStatement) {
+    if let Some(exp) = &l.exp {
+        v.visit_expression(exp);
+    }
+}
+pub fn walk_if<'a,V:Visitor<'a> + ?Sized>(v:&mut V,i:&'a IfStatement) {
+    v.visit_expression(&i.condition);
+    walk_program(v,&i.consequence);
+    walk_program(v,&i.alternative);
+}
+pub fn walk_for<'a,V:Visitor<'a> + ?Sized>(v:&mut V,f:&'a ForStatement) {
+    v.visit_statement(&f.start_condition);
+    v.visit_expression(&f.condition);
+    v.visit_statement(&f.self_operation);
+    walk_program(v,&f.consequence);
+}
+pub fn walk_fun<'a,V:Visitor<'a> + ?Sized>(v:&mut V,f:&'a FunExpression) {
+    if let Some(name) = &f.name {
+        v.visit_expression(name);
+    }
+    for exp in f.param_exp.iter().flatten() {
+        v.visit_expression(exp);
+    }
+    walk_program(v,&f.body);
+}
+pub fn walk_type<'a,V:Visitor<'a> + ?Sized>(v:&mut V,t:&'a TypeStatement) {
+    v.visit_expression(&t.exp)
+}
+pub fn walk_infix<'a,V:Visitor<'a> + ?Sized>(v:&mut V,infix:&'a InfixExpression) {
+    v.visit_expression(&infix.left);
+    v.visit_expression(&infix.right);
+}
+pub fn walk_param<'a,V:Visitor<'a> + ?Sized>(v:&mut V,param:&'a ParamExpression) {
+    if let Some(exp) = &param.default {
+        v.visit_expression(exp);
+    }
+}
+
+//in-place rewriting, the same shape as Visitor over &mut nodes,
+//a node can be replaced wholesale by assigning to it before or after walking
+pub trait MutVisitor {
+    fn visit_statement(&mut self,stem:&mut Statement) {
+        walk_statement_mut(self,stem)
+    }
+    fn visit_expression(&mut self,exp:&mut Expression) {
+        walk_expression_mut(self,exp)
+    }
+    fn visit_let(&mut self,l:&mut LetStatement) {
+        walk_let_mut(self,l)
+    }
+    fn visit_if(&mut self,i:&mut IfStatement) {
+        walk_if_mut(self,i)
+    }
+    fn visit_for(&mut self,f:&mut ForStatement) {
+        walk_for_mut(self,f)
+    }
+    fn visit_fun(&mut self,f:&mut FunExpression) {
+        walk_fun_mut(self,f)
+    }
+    fn visit_type(&mut self,t:&mut TypeStatement) {
+        walk_type_mut(self,t)
+    }
+    fn visit_import(&mut self,_:&mut ImportStatement) {}
+    fn visit_infix(&mut self,infix:&mut InfixExpression) {
+        walk_infix_mut(self,infix)
+    }
+    fn visit_param(&mut self,param:&mut ParamExpression) {
+        walk_param_mut(self,param)
+    }
+}
+
+pub fn walk_program_mut<V:MutVisitor + ?Sized>(v:&mut V,stems:&mut [Statement]) {
+    for stem in stems {
+        v.visit_statement(stem);
+    }
+}
+pub fn walk_statement_mut<V:MutVisitor + ?Sized>(v:&mut V,stem:&mut Statement) {
+    match &mut stem.kind {
+        StatementKind::Let(l) | StatementKind::Const(l) => v.visit_let(l),
+        StatementKind::Assign(target,exp) => {
+            v.visit_expression(target);
+            v.visit_expression(exp);
+        },
+        StatementKind::IF(i) => v.visit_if(i),
+        StatementKind::Fun(f) => v.visit_fun(f),
+        StatementKind::Type(t) => v.visit_type(t),
+        StatementKind::Invoke(exp) | StatementKind::Return(Some(exp)) => v.visit_expression(exp),
+        StatementKind::For(f) => v.visit_for(f),
+        StatementKind::Import(import) => v.visit_import(import),
+        StatementKind::Pub(stem) => v.visit_statement(stem),
+        StatementKind::Defer(stems) => walk_program_mut(v,stems),
+        StatementKind::Return(None) | StatementKind::Break | StatementKind::Continue | StatementKind::Error => {}
+    }
+}
+pub fn walk_expression_mut<V:MutVisitor + ?Sized>(v:&mut V,exp:&mut Expression) {
+    match &mut exp.kind {
+        ExpressionKind::Infix(infix) => v.visit_infix(infix),
+        ExpressionKind::Param(param) => v.visit_param(param),
+        ExpressionKind::NamedArg(_,exp) | ExpressionKind::SelfOp(exp,_,_) | ExpressionKind::Try(exp)
+        | ExpressionKind::Field(exp,_) => v.visit_expression(exp),
+        ExpressionKind::Struct(list) | ExpressionKind::ListValue(list) | ExpressionKind::Tuple(list) => {
+            for exp in list {
+                v.visit_expression(exp);
+            }
+        },
+        ExpressionKind::FunType(params,_) => {
+            for exp in params.iter_mut().flatten() {
+                v.visit_expression(exp);
+            }
+        },
+        ExpressionKind::Call(f,args) => {
+            v.visit_expression(f);
+            for exp in args {
+                v.visit_expression(exp);
+            }
+        },
+        ExpressionKind::Index(left,index) => {
+            v.visit_expression(left);
+            v.visit_expression(index);
+        },
+        ExpressionKind::Fun(f) => v.visit_fun(f),
+        ExpressionKind::Int(_) | ExpressionKind::String(_) | ExpressionKind::Float(_) | ExpressionKind::Bool(_)
+        | ExpressionKind::Ident(_) | ExpressionKind::Basic(_) | ExpressionKind::Nil | ExpressionKind::Unknown => {}
+    }
+}
+pub fn walk_let_mut<V:MutVisitor + ?Sized>(v:&mut V,l:&mut LetStatement) {
+    if let Some(exp) = &mut l.exp {
+        v.visit_expression(exp);
+    }
+}
+pub fn walk_if_mut<V:MutVisitor + ?Sized>(v:&mut V,i:&mut IfStatement) {
+    v.visit_expression(&mut i.condition);
+    walk_program_mut(v,&mut i.consequence);
+    walk_program_mut(v,&mut i.alternative);
+}
+pub fn walk_for_mut<V:MutVisitor + ?Sized>(v:&mut V,f:&mut ForStatement) {
+    v.visit_statement(&mut f.start_condition);
+    v.visit_expression(&mut f.condition);
+    v.visit_statement(&mut f.self_operation);
+    walk_program_mut(v,&mut f.consequence);
+}
+pub fn walk_fun_mut<V:MutVisitor + ?Sized>(v:&mut V,f:&mut FunExpression) {
+    if let Some(name) = &mut f.name {
+        v.visit_expression(name);
+    }
+    for exp in f.param_exp.iter_mut().flatten() {
+        v.visit_expression(exp);
+    }
+    walk_program_mut(v,&mut f.body);
+}
+pub fn walk_type_mut<V:MutVisitor + ?Sized>(v:&mut V,t:&mut TypeStatement) {
+    v.visit_expression(&mut t.exp)
+}
+pub fn walk_infix_mut<V:MutVisitor + ?Sized>(v:&mut V,infix:&mut InfixExpression) {
+    v.visit_expression(&mut infix.left);
+    v.visit_expression(&mut infix.right);
+}
+pub fn walk_param_mut<V:MutVisitor + ?Sized>(v:&mut V,param:&mut ParamExpression) {
+    if let Some(exp) = &mut param.default {
+        v.visit_expression(exp);
+    }
+}
+
+#[cfg(test)]
+fn fixtures() -> Vec<crate::Program> {
+    let mut paths:Vec<_> = std::fs::read_dir("./src/script").unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "kz"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+    paths.iter().map(|path| crate::parse_file(path.to_str().unwrap()).unwrap()).collect()
+}
+//Debug prints every node as `Statement { .. }` or `Expression { .. }`,
+//which gives a count that does not depend on the walk functions
+#[cfg(test)]
+fn debug_count(stems:&[Statement],name:&str) -> usize {
+    let text = format!("{:?}",stems);
+    let pat = format!("{} {{",name);
+    text.match_indices(&pat)
+        .filter(|(i,_)| !text[..*i].ends_with(|c:char| c.is_alphanumeric()))
+        .count()
+}
+#[test]
+fn test_visitor_every_node(){
+    #[derive(Default)]
+    struct Count {
+        stems:usize,
+        exps:usize,
+    }
+    impl Visitor<'_> for Count {
+        fn visit_statement(&mut self,stem:&Statement) {
+            self.stems += 1;
+            walk_statement(self,stem)
+        }
+        fn visit_expression(&mut self,exp:&Expression) {
+            self.exps += 1;
+            walk_expression(self,exp)
+        }
+    }
+    impl MutVisitor for Count {
+        fn visit_statement(&mut self,stem:&mut Statement) {
+            self.stems += 1;
+            walk_statement_mut(self,stem)
+        }
+        fn visit_expression(&mut self,exp:&mut Expression) {
+            self.exps += 1;
+            walk_expression_mut(self,exp)
+        }
+    }
+    for mut program in fixtures() {
+        let stems = debug_count(&program.statements,"Statement");
+        let exps = debug_count(&program.statements,"Expression");
+        assert!(stems > 0);
+
+        let mut count = Count::default();
+        walk_program(&mut count,&program.statements);
+        assert_eq!((count.stems,count.exps),(stems,exps));
+
+        let mut count = Count::default();
+        walk_program_mut(&mut count,&mut program.statements);
+        assert_eq!((count.stems,count.exps),(stems,exps));
+    }
+}
+#[test]
+fn test_visitor_nested(){
+    //names reached through for, if, fun bodies, lambdas and defaults
+    struct Idents(Vec<String>);
+    impl Visitor<'_> for Idents {
+        fn visit_expression(&mut self,exp:&Expression) {
+            if let ExpressionKind::Ident(name) = &exp.kind {
+                self.0.push(name.clone());
+            }
+            walk_expression(self,exp)
+        }
+    }
+    let source = "fun f(a:int,b:int = c) int {\n for let i:int = 0;i < a;i++ {\n  if i == d {\n   return e\n  } else {\n   defer g(|| h)\n  }\n }\n return b\n}\n";
+    let program = crate::parse_str(source);
+    let mut idents = Idents(vec![]);
+    walk_program(&mut idents,&program.statements);
+    assert_eq!(idents.0,["f","c","i","a","i","i","d","e","g","h","b"]);
+}
+#[test]
+fn test_mut_visitor_rewrite(){
+    //rename x to y everywhere, the result equals the renamed source
+    struct Rename;
+    impl MutVisitor for Rename {
+        fn visit_expression(&mut self,exp:&mut Expression) {
+            if exp.kind == ExpressionKind::Ident("x".to_string()) {
+                exp.kind = ExpressionKind::Ident("y".to_string());
+            }
+            walk_expression_mut(self,exp)
+        }
+    }
+    let source = "fun f(a:int) int {\n for let i:int = 0;i < x;i++ {\n  if x > 1 {\n   a = x[i] + g(x)\n  }\n }\n return |b| b * x\n}\n";
+    let mut program = crate::parse_str(source);
+    walk_program_mut(&mut Rename,&mut program.statements);
+    let expected = crate::parse_str(&source.replace('x',"y"));
+    assert_eq!(program.statements,expected.statements);
+    assert_ne!(program.statements,crate::parse_str(source).statements);
+}
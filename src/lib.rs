@@ -0,0 +1,28 @@
+pub mod ast;
+pub mod analysis;
+pub mod parser;
+pub mod program;
+#[cfg(test)]
+mod snapshot;
+
+use crate::parser::cst::Cst;
+use crate::parser::error::KzError;
+use crate::parser::file::PaserFile;
+
+pub use crate::parser::parse::Parser;
+pub use crate::program::Program;
+
+//parse and check a script held in memory
+pub fn parse_str(source:&str) -> Program {
+    Program::parse(PaserFile::from_bytes("<str>",source.as_bytes().to_vec()))
+}
+
+//parse and check a script on disk, imports are not followed, see parser::module::ModuleLoader
+pub fn parse_file(path:&str) -> std::io::Result<Program> {
+    Ok(Program::parse(PaserFile::open(path)?))
+}
+
+//the lossless tree of a script, see parser::cst
+pub fn parse_lossless(source:&str) -> (Cst,Vec<KzError>) {
+    Cst::parse(PaserFile::from_bytes("<str>",source.as_bytes().to_vec()))
+}
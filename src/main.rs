@@ -1,5 +1,34 @@
-mod parser;
-mod ast;
-fn main() {
-    
+use std::process::ExitCode;
+
+//kzScript [--json | --sexp | --dot] <file>...  parses and checks every file, printing what it finds,
+//--json and --sexp also write the tree of each file to stdout, see ast::export,
+//--dot the control flow graphs of its funs, see analysis::cfg
+fn main() -> ExitCode {
+    let mut ok = true;
+    let mut format = None;
+    for arg in std::env::args().skip(1) {
+        if arg == "--json" || arg == "--sexp" || arg == "--dot" {
+            format = Some(arg);
+            continue
+        }
+        match kz::parse_file(&arg) {
+            Ok(program) => {
+                match format.as_deref() {
+                    Some("--json") => println!("{}",program.to_json()),
+                    Some("--dot") => print!("{}",program.to_dot()),
+                    Some(_) => println!("{}",program.to_sexp()),
+                    None => {}
+                }
+                for err in &program.diagnostics {
+                    eprintln!("{}",err);
+                }
+                ok &= program.is_ok();
+            },
+            Err(e) => {
+                eprintln!("{}: {}",arg,e);
+                ok = false;
+            }
+        }
+    }
+    if ok { ExitCode::SUCCESS } else { ExitCode::FAILURE }
 }
@@ -0,0 +1,308 @@
+use crate::ast::fun::FunExpression;
+use crate::ast::node::{Expression, ExpressionKind, Statement, StatementKind};
+use crate::parser::error::KzError;
+use crate::parser::file::PaserFile;
+use crate::parser::lexer::Lexer;
+use crate::parser::parse::Parser;
+use crate::parser::span::Span;
+use crate::parser::token::Token;
+
+//lossless syntax tree: every byte of the source is in exactly one token or trivia,
+//nodes follow the AST, so a formatter can edit the tree and write it back out
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum TriviaKind {
+    //spaces, tabs and \r
+    Whitespace,
+    Newline,
+    //  // ...
+    Notes,
+    //  /* ... */  /** ... **/
+    NotesBlock,
+    //  /// ...
+    DocNotes,
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct Trivia {
+    pub kind:TriviaKind,
+    pub span:Span,
+}
+
+#[derive(Debug,PartialEq)]
+pub struct CstToken {
+    pub tok:Token,
+    pub span:Span,
+    //newlines and everything on the lines before the token
+    pub leading:Vec<Trivia>,
+    //whitespace and notes after the token up to the end of its line
+    pub trailing:Vec<Trivia>,
+}
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum CstKind {
+    Program,
+    //StatementKind::name
+    Statement(&'static str),
+    //ExpressionKind::name
+    Expression(&'static str),
+}
+
+#[derive(Debug,PartialEq)]
+pub enum CstChild {
+    Node(CstNode),
+    Token(CstToken),
+}
+
+#[derive(Debug,PartialEq)]
+pub struct CstNode {
+    pub kind:CstKind,
+    pub span:Span,
+    pub children:Vec<CstChild>,
+}
+
+impl CstNode {
+    fn new(kind:CstKind,span:Span) -> CstNode {
+        Self {
+            kind,
+            span,
+            children:vec![],
+        }
+    }
+    pub fn nodes(&self) -> impl Iterator<Item=&CstNode> {
+        self.children.iter().filter_map(|child| match child {
+            CstChild::Node(node) => Some(node),
+            CstChild::Token(_) => None
+        })
+    }
+    pub fn tokens(&self) -> impl Iterator<Item=&CstToken> {
+        self.children.iter().filter_map(|child| match child {
+            CstChild::Token(tok) => Some(tok),
+            CstChild::Node(_) => None
+        })
+    }
+}
+
+pub struct Cst {
+    pub root:CstNode,
+    pub source:Vec<u8>,
+}
+
+impl Cst {
+    //the lossless mode of the parser, syntax errors are returned next to a tree
+    //that still covers the whole input
+    pub fn parse(f:PaserFile) -> (Cst,Vec<KzError>) {
+        let source = f.body.clone();
+        let mut raw = PaserFile::from_bytes(f.file_path(),f.body.clone());
+        raw.id = f.id;
+        let (stems,errors) = Parser::new(Lexer::new(f)).program_recover();
+
+        let mut root = CstNode::new(CstKind::Program,Span {
+            end:usize::MAX,
+            ..Span::default()
+        });
+        root.children = stems.iter().map(|stem| CstChild::Node(statement(stem))).collect();
+        let mut tokens = tokens(Lexer::new(raw),source.len()).into_iter().peekable();
+        fill(&mut root,&mut tokens);
+        root.span = Span {
+            end:source.len(),
+            ..root.span
+        };
+        (Cst { root, source },errors)
+    }
+    pub fn text(&self,span:Span) -> &[u8] {
+        &self.source[span.start..span.end]
+    }
+    //the source the tree was built from, byte for byte
+    pub fn write(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.source.len());
+        self.write_node(&self.root,&mut out);
+        out
+    }
+    fn write_node(&self,node:&CstNode,out:&mut Vec<u8>) {
+        for child in &node.children {
+            match child {
+                CstChild::Node(node) => self.write_node(node,out),
+                CstChild::Token(tok) => {
+                    for trivia in &tok.leading {
+                        out.extend_from_slice(self.text(trivia.span));
+                    }
+                    out.extend_from_slice(self.text(tok.span));
+                    for trivia in &tok.trailing {
+                        out.extend_from_slice(self.text(trivia.span));
+                    }
+                }
+            }
+        }
+    }
+}
+
+//every token the parser sees, with the bytes it skips turned into trivia,
+//the last token is Eof and carries the trivia at the end of the file
+fn tokens(mut lex:Lexer,len:usize) -> Vec<CstToken> {
+    let mut v:Vec<CstToken> = Vec::new();
+    let mut trivia:Vec<Trivia> = Vec::new();
+    let mut end = 0;
+    loop {
+        let tok = lex.next();
+        let span = lex.span();
+        if span.start > end {
+            trivia.push(Trivia { kind:TriviaKind::Whitespace, span:Span { start:end, end:span.start, ..span } });
+        }
+        end = span.end;
+        match tok {
+            Token::N => trivia.push(Trivia { kind:TriviaKind::Newline, span }),
+            Token::Notes => trivia.push(Trivia { kind:TriviaKind::Notes, span }),
+            Token::NotesBlock => trivia.push(Trivia { kind:TriviaKind::NotesBlock, span }),
+            Token::DocNotes(_) => trivia.push(Trivia { kind:TriviaKind::DocNotes, span }),
+            _ => {
+                let eof = tok == Token::Eof;
+                v.push(CstToken {
+                    tok,
+                    span:Span { end:span.end.min(len), ..span },
+                    leading:std::mem::take(&mut trivia),
+                    trailing:vec![],
+                });
+                if eof {
+                    break
+                }
+            }
+        }
+    }
+    //trivia on the rest of a token's line moves to its trailing side
+    for i in 1..v.len() {
+        let split = v[i].leading.iter().position(|t| t.kind == TriviaKind::Newline).unwrap_or(v[i].leading.len());
+        let trailing:Vec<Trivia> = v[i].leading.drain(..split).collect();
+        v[i - 1].trailing = trailing;
+    }
+    v
+}
+
+//hands tokens to the innermost node whose span covers them
+fn fill(node:&mut CstNode,tokens:&mut std::iter::Peekable<std::vec::IntoIter<CstToken>>) {
+    let children = std::mem::take(&mut node.children);
+    for child in children {
+        if let CstChild::Node(mut child) = child {
+            while let Some(tok) = tokens.next_if(|tok| tok.span.start < child.span.start && tok.tok != Token::Eof) {
+                node.children.push(CstChild::Token(tok));
+            }
+            fill(&mut child,tokens);
+            node.children.push(CstChild::Node(child));
+        }
+    }
+    while let Some(tok) = tokens.next_if(|tok| tok.span.start < node.span.end && (tok.tok != Token::Eof || node.kind == CstKind::Program)) {
+        node.children.push(CstChild::Token(tok));
+    }
+}
+
+fn nodes_of(mut nodes:Vec<CstNode>) -> Vec<CstChild> {
+    //children built from the AST in source order, synthesized nodes without a span are left out
+    nodes.retain(|node| !node.span.is_empty());
+    nodes.sort_by_key(|node| node.span.start);
+    nodes.into_iter().map(CstChild::Node).collect()
+}
+
+fn statement(stem:&Statement) -> CstNode {
+    let mut node = CstNode::new(CstKind::Statement(stem.kind.name()),stem.span);
+    let mut nodes = Vec::new();
+    match &stem.kind {
+        StatementKind::Let(l) | StatementKind::Const(l) => nodes.extend(l.exp.iter().map(expression)),
+        StatementKind::Assign(target,exp) => nodes.extend([expression(target),expression(exp)]),
+        StatementKind::IF(i) => {
+            nodes.push(expression(&i.condition));
+            nodes.extend(i.consequence.iter().chain(i.alternative.iter()).map(statement));
+        },
+        StatementKind::Fun(f) => nodes.extend(fun(f)),
+        StatementKind::Type(t) => nodes.push(expression(&t.exp)),
+        StatementKind::Invoke(exp) => nodes.push(expression(exp)),
+        StatementKind::Return(exp) => nodes.extend(exp.iter().map(expression)),
+        StatementKind::For(f) => {
+            nodes.extend([statement(&f.start_condition),expression(&f.condition),statement(&f.self_operation)]);
+            nodes.extend(f.consequence.iter().map(statement));
+        },
+        StatementKind::Pub(stem) => nodes.push(statement(stem)),
+        StatementKind::Defer(stems) => nodes.extend(stems.iter().map(statement)),
+        StatementKind::Break | StatementKind::Continue | StatementKind::Import(_) | StatementKind::Error => {}
+    }
+    node.children = nodes_of(nodes);
+    node
+}
+
+fn fun(f:&FunExpression) -> Vec<CstNode> {
+    let mut nodes:Vec<CstNode> = f.name.iter().map(expression).collect();
+    nodes.extend(f.param_exp.iter().flatten().map(expression));
+    nodes.extend(f.body.iter().map(statement));
+    nodes
+}
+
+fn expression(exp:&Expression) -> CstNode {
+    let mut node = CstNode::new(CstKind::Expression(exp.kind.name()),exp.span);
+    let mut nodes = Vec::new();
+    match &exp.kind {
+        ExpressionKind::Infix(infix) => nodes.extend([expression(&infix.left),expression(&infix.right)]),
+        ExpressionKind::Param(param) => nodes.extend(param.default.iter().map(expression)),
+        ExpressionKind::NamedArg(_,exp) | ExpressionKind::SelfOp(exp,_,_) | ExpressionKind::Try(exp)
+        | ExpressionKind::Field(exp,_) => nodes.push(expression(exp)),
+        ExpressionKind::Struct(list) | ExpressionKind::ListValue(list) | ExpressionKind::Tuple(list) => nodes.extend(list.iter().map(expression)),
+        ExpressionKind::FunType(params,_) => nodes.extend(params.iter().flatten().map(expression)),
+        ExpressionKind::Call(f,args) => {
+            nodes.push(expression(f));
+            nodes.extend(args.iter().map(expression));
+        },
+        ExpressionKind::Index(left,index) => nodes.extend([expression(left),expression(index)]),
+        ExpressionKind::Fun(f) => nodes.extend(fun(f)),
+        ExpressionKind::Int(_) | ExpressionKind::String(_) | ExpressionKind::Float(_) | ExpressionKind::Bool(_)
+        | ExpressionKind::Ident(_) | ExpressionKind::Basic(_) | ExpressionKind::Nil | ExpressionKind::Unknown => {}
+    }
+    node.children = nodes_of(nodes);
+    node
+}
+
+#[test]
+fn test_cst_lossless() {
+    fn kz_files(dir:&std::path::Path,files:&mut Vec<std::path::PathBuf>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                kz_files(&path,files);
+            } else if path.extension().is_some_and(|ext| ext == "kz") {
+                files.push(path);
+            }
+        }
+    }
+    let mut files = Vec::new();
+    kz_files(std::path::Path::new("./src/script"),&mut files);
+    assert!(!files.is_empty());
+    for path in files {
+        let path = path.to_string_lossy().to_string();
+        let (cst,_) = Cst::parse(PaserFile::new(&path));
+        assert!(cst.write() == cst.source,"{} is not reproduced",path);
+    }
+}
+
+#[test]
+fn test_cst_trivia() {
+    let source = "//sum\nlet a:int = 1 + 2  // three\n\n/** done **/\n";
+    let (cst,errors) = Cst::parse(PaserFile::from_bytes("cst.kz",source.as_bytes().to_vec()));
+    assert!(errors.is_empty());
+    assert_eq!(cst.write(),source.as_bytes());
+
+    let stem = cst.root.nodes().next().unwrap();
+    assert_eq!(stem.kind,CstKind::Statement("Let"));
+    let let_tok = stem.tokens().next().unwrap();
+    let leading:Vec<TriviaKind> = let_tok.leading.iter().map(|t| t.kind).collect();
+    assert_eq!(leading,vec![TriviaKind::Notes,TriviaKind::Newline]);
+
+    let infix = stem.nodes().next().unwrap();
+    assert_eq!(infix.kind,CstKind::Expression("Infix"));
+    assert_eq!(cst.text(infix.span),b"1 + 2");
+    let two = infix.nodes().nth(1).unwrap().tokens().next().unwrap();
+    let trailing:Vec<TriviaKind> = two.trailing.iter().map(|t| t.kind).collect();
+    assert_eq!(trailing,vec![TriviaKind::Whitespace,TriviaKind::Notes]);
+    assert_eq!(cst.text(two.trailing[1].span),b"// three");
+
+    let eof = cst.root.tokens().last().unwrap();
+    assert_eq!(eof.tok,Token::Eof);
+    let leading:Vec<TriviaKind> = eof.leading.iter().map(|t| t.kind).collect();
+    assert_eq!(leading,vec![TriviaKind::Newline,TriviaKind::Newline,TriviaKind::NotesBlock,TriviaKind::Newline]);
+}
@@ -1,6 +1,8 @@
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
 
+use crate::ast::printer::type_name;
+use crate::parser::span::Span;
 use crate::parser::token::Token;
 
 
@@ -8,6 +10,7 @@ pub struct KzError {
     path:String,
     line:i32,
     index:i32,
+    span:Span,
     err:KzErr
 }
 impl KzError {
@@ -16,27 +19,53 @@ impl KzError {
             path:path.to_string(),
             line,
             index,
+            span:Span::default(),
             err
         }
     }
+    pub fn at(path:&str,span:Span,err:KzErr) -> KzError {
+        Self {
+            path:path.to_string(),
+            line:span.line,
+            index:span.col,
+            span,
+            err
+        }
+    }
+    pub fn span(&self) -> Span {
+        self.span
+    }
+    pub fn err(&self) -> &KzErr {
+        &self.err
+    }
+    pub fn severity(&self) -> Severity {
+        self.err.severity()
+    }
     pub fn error(self) -> String {
-        return format!("{}  line->{}:{}\n\t\t{}",self.path,self.line,self.index,self.err.echo())
+        self.to_string()
+    }
+    fn message(&self) -> String {
+        match self.severity() {
+            Severity::Error => self.err.echo(),
+            Severity::Warning => format!("warning: {}",self.err.echo()),
+        }
     }
 }
 impl Debug for KzError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f,"{}  line->{}:{}\n\t\t{}",self.path,self.line,self.index,self.err.echo())
+        write!(f,"{}  line->{}:{}\n\t\t{}",self.path,self.line,self.index,self.message())
     }
 }
 impl Display for KzError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f,"{}  line->{}:{}\n\t\t{}",self.path,self.line,self.index,self.err.echo())
+        write!(f,"{}  line->{}:{}\n\t\t{}",self.path,self.line,self.index,self.message())
     }
 }
 
 impl Error for KzError{
 }
 
+#[derive(Debug,Clone,PartialEq)]
 pub enum KzErr {
     Program(Token),
     Type(Token),
@@ -46,10 +75,98 @@ pub enum KzErr {
     UnOpSymbol(Token),
     Value(String),
     ParamName,
-
+    ConstExpr(String),
+    ConstAssign(String),
+    Import(String),
+    ImportCycle(Vec<String>),
+    ImportName(String,String),
+    ParamDefault(String),
+    ParamVariadic(String),
+    Try(Option<Token>),
+    Defer,
+    Eof(String),
+    Undefined(String),
+    Duplicate(String),
+    UseBeforeDeclaration(String),
+    //expected, found
+    TypeMismatch(Box<Token>,Box<Token>),
+    //operator, left, right
+    Operand(Token,Box<Token>,Box<Token>),
+    //fun, expected, found
+    ArgCount(String,usize,usize),
+    ArgName(String,String),
+    Index(Token),
+    NoField(Box<Token>,String),
+    AliasCycle(Vec<String>),
+    //names, value
+    Destructure(usize,Box<Token>),
+    //fun, declared return type
+    MissingReturn(String,Token),
+    Unreachable,
+    //break or continue
+    OutsideLoop(Token),
+    //warnings, see Severity
+    ReadBeforeAssign(String),
+    UnusedVariable(String),
+    DeadStore(String),
+    UnusedParam(String),
+}
+//a warning does not stop a program from being ok, see Program::is_ok
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
 }
+
 impl KzErr {
-    fn echo(&self) -> String {
+    pub fn severity(&self) -> Severity {
+        match self {
+            KzErr::ReadBeforeAssign(_) | KzErr::UnusedVariable(_) | KzErr::DeadStore(_) | KzErr::UnusedParam(_) => Severity::Warning,
+            _ => Severity::Error
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            KzErr::Program(_) => "Program",
+            KzErr::Type(_) => "Type",
+            KzErr::Expected(_) => "Expected",
+            KzErr::ExpectedName => "ExpectedName",
+            KzErr::UnExpSymbol(_) => "UnExpSymbol",
+            KzErr::UnOpSymbol(_) => "UnOpSymbol",
+            KzErr::Value(_) => "Value",
+            KzErr::ParamName => "ParamName",
+            KzErr::ConstExpr(_) => "ConstExpr",
+            KzErr::ConstAssign(_) => "ConstAssign",
+            KzErr::Import(_) => "Import",
+            KzErr::ImportCycle(_) => "ImportCycle",
+            KzErr::ImportName(_,_) => "ImportName",
+            KzErr::ParamDefault(_) => "ParamDefault",
+            KzErr::ParamVariadic(_) => "ParamVariadic",
+            KzErr::Try(_) => "Try",
+            KzErr::Defer => "Defer",
+            KzErr::Eof(_) => "Eof",
+            KzErr::Undefined(_) => "Undefined",
+            KzErr::Duplicate(_) => "Duplicate",
+            KzErr::UseBeforeDeclaration(_) => "UseBeforeDeclaration",
+            KzErr::TypeMismatch(_,_) => "TypeMismatch",
+            KzErr::Operand(_,_,_) => "Operand",
+            KzErr::ArgCount(_,_,_) => "ArgCount",
+            KzErr::ArgName(_,_) => "ArgName",
+            KzErr::Index(_) => "Index",
+            KzErr::NoField(_,_) => "NoField",
+            KzErr::AliasCycle(_) => "AliasCycle",
+            KzErr::Destructure(_,_) => "Destructure",
+            KzErr::MissingReturn(_,_) => "MissingReturn",
+            KzErr::Unreachable => "Unreachable",
+            KzErr::OutsideLoop(_) => "OutsideLoop",
+            KzErr::ReadBeforeAssign(_) => "ReadBeforeAssign",
+            KzErr::UnusedVariable(_) => "UnusedVariable",
+            KzErr::DeadStore(_) => "DeadStore",
+            KzErr::UnusedParam(_) => "UnusedParam",
+        }
+    }
+    //the message without a position
+    pub fn echo(&self) -> String {
         match self {
             KzErr::Program(tok) => format!("program unknown type error {:?}",tok),
             KzErr::Type(tok) => format!("'{:?}' type is not legal",tok),
@@ -59,7 +176,35 @@ impl KzErr {
             KzErr::UnOpSymbol(tok) => format!("unknown operation symbol -> '{:?}'",tok),
             KzErr::Value(s) => format!("unknown value input -> {}",s),
             KzErr::ParamName => "Parameter name is not a valid value".to_string(),
-            _ => "Unable to process error".to_string()
+            KzErr::ConstExpr(name) => format!("const '{}' initializer is not a constant expression",name),
+            KzErr::ConstAssign(name) => format!("cannot assign to const '{}'",name),
+            KzErr::Import(path) => format!("cannot read module '{}'",path),
+            KzErr::ImportCycle(chain) => format!("import cycle: {}",chain.join(" -> ")),
+            KzErr::ImportName(name,path) => format!("'{}' is not exported by '{}'",name,path),
+            KzErr::ParamDefault(name) => format!("parameter '{}' without a default value follows a parameter with one",name),
+            KzErr::ParamVariadic(name) => format!("variadic parameter '{}' must be the last parameter",name),
+            KzErr::Try(None) => "'?' can only be used inside a function".to_string(),
+            KzErr::Try(Some(tok)) => format!("'?' needs a function returning an error, found '{:?}'",tok),
+            KzErr::Defer => "defer can only be used inside a function".to_string(),
+            KzErr::Eof(expected) => format!("unexpected end of file, expected {}",expected),
+            KzErr::Undefined(name) => format!("'{}' is not defined",name),
+            KzErr::Duplicate(name) => format!("'{}' is already declared in this scope",name),
+            KzErr::UseBeforeDeclaration(name) => format!("'{}' is used before its declaration",name),
+            KzErr::TypeMismatch(expected,found) => format!("expected '{}', found '{}'",type_name(expected),type_name(found)),
+            KzErr::Operand(op,left,right) => format!("'{:?}' cannot be applied to '{}' and '{}'",op,type_name(left),type_name(right)),
+            KzErr::ArgCount(name,expected,found) => format!("'{}' takes {} arguments, found {}",name,expected,found),
+            KzErr::ArgName(name,arg) => format!("'{}' has no parameter named '{}'",name,arg),
+            KzErr::Index(tok) => format!("'{}' cannot be indexed",type_name(tok)),
+            KzErr::NoField(tok,name) => format!("'{}' has no field '{}'",type_name(tok),name),
+            KzErr::AliasCycle(chain) => format!("type alias cycle: {}",chain.join(" -> ")),
+            KzErr::Destructure(names,tok) => format!("cannot destructure '{}' into {} names",type_name(tok),names),
+            KzErr::MissingReturn(name,tok) => format!("'{}' can reach the end of its body without returning '{}'",name,type_name(tok)),
+            KzErr::Unreachable => "statement is unreachable".to_string(),
+            KzErr::OutsideLoop(tok) => format!("'{:?}' outside of a loop",tok),
+            KzErr::ReadBeforeAssign(name) => format!("'{}' is read before it is assigned",name),
+            KzErr::UnusedVariable(name) => format!("'{}' is never read",name),
+            KzErr::DeadStore(name) => format!("value assigned to '{}' is never read",name),
+            KzErr::UnusedParam(name) => format!("parameter '{}' is never used",name),
         }
     }
 }
\ No newline at end of file
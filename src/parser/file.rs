@@ -2,6 +2,8 @@ use std::{fs::{self}};
 
 pub struct PaserFile {
     path:String,
+    //index of the file in a ModuleGraph, copied into every Span
+    pub id:usize,
     pub body:Vec<u8>
 }
 
@@ -10,10 +12,27 @@ impl PaserFile {
         let body = fs::read(path).unwrap();
         Self {
             path:path.to_string(),
-            body:body
+            id:0,
+            body
         }
     }
 
+    pub fn from_bytes(path:&str,body:Vec<u8>) -> PaserFile {
+        Self {
+            path:path.to_string(),
+            id:0,
+            body
+        }
+    }
+
+    pub fn open(path:&str) -> std::io::Result<PaserFile> {
+        Ok(Self {
+            path:path.to_string(),
+            id:0,
+            body:fs::read(path)?
+        })
+    }
+
     pub fn file_path(&self) -> &str {
         &self.path
     }
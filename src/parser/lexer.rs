@@ -1,11 +1,14 @@
 
-use super::{file::PaserFile, token::Token};
+use super::{file::PaserFile, span::Span, token::Token};
 pub struct Lexer {
     f:PaserFile,
     index:usize,
     ch:u8,
     line:i32,
     line_index:i32,
+    //span of the token last returned by next
+    span:Span,
+    unclosed:bool,
 }
 impl Lexer {
     pub fn new(file:PaserFile) -> Lexer {
@@ -15,27 +18,40 @@ impl Lexer {
             ch:0,
             line:1,
             line_index:0,
+            span:Span::default(),
+            unclosed:false,
         }
     }
 
     pub fn next(&mut self) -> Token {
-        if self.index >= self.f.body.len() {
-            return Token::Eof;
-        }
         loop {
-            self.read();
-            self.line_index += 1;
-            if self.ch == b' ' {
-                continue;
+            if self.index >= self.f.body.len() {
+                let (line,col) = self.position(self.f.body.len());
+                self.span = Span { line, col, ..self.span_at(self.f.body.len()) };
+                return Token::Eof;
             }
-            if self.ch == b'\r' {
-                self.line +=1;
-                self.line_index = 0;
+            self.read();
+            if matches!(self.ch,b' ' | b'\t' | b'\r') {
                 continue;
             }
             break;
         };
-
+        let mut span = self.span_at(self.index - 1);
+        let tok = self.token();
+        span.end = self.index;
+        self.span = span;
+        tok
+    }
+    fn span_at(&self,start:usize) -> Span {
+        Span {
+            file_id:self.f.id,
+            start,
+            end:start,
+            line:self.line,
+            col:self.line_index,
+        }
+    }
+    fn token(&mut self) -> Token {
         if self.is_letter(self.ch) {
             let ident = self.read_string();
             return self.as_token(ident.as_str());
@@ -51,6 +67,9 @@ impl Lexer {
                 if self.expect_peek(b'=') {
                     return Token::Equ
                 }
+                if self.expect_peek(b'>') {
+                    return Token::FatArrow
+                }
                 Token::Assign
             },
             b'+' => {
@@ -69,23 +88,18 @@ impl Lexer {
                 Token::Sub
             },
             b'*' => {
-                if self.expect_peek(b'*') {
-                    if self.expect_peek(b'/') {
-                        return Token::NotesBlockEnd
-                    }
-                    return Token::Err("notes block end not '*/' the is a '**/'".to_string())
+                if self.expect_peek(b'/') {
+                    return Token::Err("'*/' without an opening '/*'".to_string())
                 }
                 Token::Mul
             },
             b'/' => {
                 if self.expect_peek(b'/') {
-                    return Token::Notes
+                    return self.read_notes()
                 }
                 if self.expect_peek(b'*') {
-                    if self.expect_peek(b'*') {
-                        return Token::NotesBlock
-                    }
-                    return Token::Err("notes block not '/*' the is a '/**'".to_string())
+                    self.read_notes_block();
+                    return Token::NotesBlock
                 }
                 Token::Div
             },
@@ -112,8 +126,30 @@ impl Lexer {
             b'(' => Token::LeftBracket,
             b')' => Token::RightBracket,
             b',' => Token::Comma,
+            b'?' => Token::Question,
+            b'|' => {
+                if self.expect_peek(b'|') {
+                    return Token::Or
+                }
+                Token::Pipe
+            },
+            b'&' => {
+                if self.expect_peek(b'&') {
+                    return Token::And
+                }
+                Token::Err("'&' is not a valid symbol, the is a '&&'".to_string())
+            },
+            b'.' => {
+                if self.expect_peek(b'.') {
+                    if self.expect_peek(b'.') {
+                        return Token::Ellipsis
+                    }
+                    return Token::Err("'..' is not a valid symbol, the is a '...'".to_string())
+                }
+                Token::Dot
+            },
             b'"' => {
-                if self.peek() == b'"' {
+                if self.expect_peek(b'"') {
                     return Token::Basics("".to_string(),Box::new(Token::String))
                 }
                 self.read();
@@ -134,8 +170,13 @@ impl Lexer {
         if  self.index >= self.f.body.len() {
             return
         }
+        if self.ch == b'\n' {
+            self.line += 1;
+            self.line_index = 0;
+        }
         self.ch = self.f.body[self.index];
         self.index += 1;
+        self.line_index += 1;
     }
 
     fn peek(&self) -> u8 {
@@ -153,11 +194,11 @@ impl Lexer {
         false
     }
     fn is_letter(&mut self,val:u8) -> bool {
-        val >= b'a' && val <= b'z' || val >= b'A' && val <= b'Z' || val == b'_'
+        val.is_ascii_alphabetic() || val == b'_'
     }
 
     fn is_num(&self,val:u8) -> bool {
-        val >= b'0' && val <= b'9'
+        val.is_ascii_digit()
     }
     fn read_string(&mut self) -> String {
        
@@ -169,19 +210,54 @@ impl Lexer {
             char.push(self.ch);
         }
 
-        return String::from_utf8( char).unwrap();
+        String::from_utf8_lossy(&char).to_string()
     }
     fn read_strings(&mut self) -> String{
 
         let mut char:Vec<u8> = Vec::new();
         char.push(self.ch);
 
-        while self.peek() != b'"' {
+        while self.peek() != b'"' && self.index < self.f.body.len() {
             self.read();
             char.push(self.ch);
         }
 
-        return String::from_utf8( char).unwrap();
+        String::from_utf8_lossy(&char).to_string()
+    }
+    //the rest of the line after //, the newline is left for Token::N
+    fn read_notes(&mut self) -> Token {
+        let doc = self.peek() == b'/' && self.f.body.get(self.index + 1) != Some(&b'/');
+        let mut char:Vec<u8> = Vec::new();
+        while self.peek() != b'\n' && self.index < self.f.body.len() {
+            self.read();
+            char.push(self.ch);
+        }
+        if !doc {
+            return Token::Notes
+        }
+        let text = String::from_utf8_lossy(&char[1..]).to_string();
+        let text = text.strip_prefix(' ').unwrap_or(&text).trim_end().to_string();
+        Token::DocNotes(text)
+    }
+    //after /*, up to the */ closing it, block notes nest
+    fn read_notes_block(&mut self) {
+        let mut depth = 1;
+        while self.index < self.f.body.len() {
+            self.read();
+            if self.ch == b'/' && self.expect_peek(b'*') {
+                depth += 1;
+            } else if self.ch == b'*' && self.expect_peek(b'/') {
+                depth -= 1;
+                if depth == 0 {
+                    return
+                }
+            }
+        }
+        self.unclosed = true;
+    }
+    //true once, after a block note ran into the end of the file
+    pub fn take_unclosed(&mut self) -> bool {
+        std::mem::take(&mut self.unclosed)
     }
     fn read_num(&mut self) -> Token {
         let mut char:Vec<u8> = vec![self.ch];
@@ -194,7 +270,7 @@ impl Lexer {
         if self.is_letter(self.ch) {
             return  Token::Err("the number cannot contain other char".to_string());
         }
-        Token::Basics(String::from_utf8(char).unwrap(),Box::new(tok))
+        Token::Basics(String::from_utf8_lossy(&char).to_string(),Box::new(tok))
     }
     fn as_token(&self,ident:&str) -> Token {
          match ident {
@@ -213,15 +289,24 @@ impl Lexer {
              "continue"=> Token::Continue,
              "break" => Token::Break,
              "return"=> Token::Return,
+             "nil" => Token::Nil,
+             "const" => Token::Const,
+             "import" => Token::Import,
+             "pub" | "export" => Token::Pub,
+             "defer" => Token::Defer,
              _ => Token::Ident(ident.to_string()),
         }
     }
 
-    pub fn line(&self) ->i32 {
-        return self.line
+    pub fn span(&self) -> Span {
+        self.span
     }
-    pub fn line_index(&self) -> i32 {
-        self.line_index
+    //1-based line and column of a byte offset
+    pub fn position(&self,offset:usize) -> (i32,i32) {
+        let before = &self.f.body[..offset.min(self.f.body.len())];
+        let line = before.iter().filter(|ch| **ch == b'\n').count() + 1;
+        let col = before.iter().rev().take_while(|ch| **ch != b'\n').count() + 1;
+        (line as i32,col as i32)
     }
     pub fn file_path(&self) -> &str {
         self.f.file_path()
@@ -1,5 +1,8 @@
 pub mod token;
-pub mod lexer;
-pub mod file;
-pub mod parser;
-pub mod error;
\ No newline at end of file
+pub(crate) mod lexer;
+pub(crate) mod file;
+pub(crate) mod parse;
+pub mod error;
+pub mod module;
+pub mod span;
+pub mod cst;
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ast::import::ImportStatement;
+use crate::ast::node::{Expression, ExpressionKind, Statement, StatementKind};
+use crate::parser::error::{KzErr, KzError};
+use crate::parser::file::PaserFile;
+use crate::parser::lexer::Lexer;
+use crate::parser::parse::Parser;
+use crate::parser::span::Span;
+
+pub struct Module {
+    pub path:String,
+    //Span::file_id of every node parsed from this file, its index in ModuleGraph::modules
+    pub file_id:usize,
+    pub statements:Vec<Statement>,
+    //indexes into ModuleGraph::modules, in import order
+    pub imports:Vec<usize>,
+}
+
+impl Module {
+    //names marked pub/export at the top level
+    pub fn exports(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for stem in &self.statements {
+            if let StatementKind::Pub(stem) = &stem.kind {
+                match &stem.kind {
+                    StatementKind::Fun(f) => {
+                        if let Some(Expression { kind:ExpressionKind::Ident(name), .. }) = &f.name {
+                            names.push(name.as_str())
+                        }
+                    },
+                    StatementKind::Type(t) => names.push(t.name.as_str()),
+                    StatementKind::Const(c) => names.push(c.ident.as_str()),
+                    _ => {}
+                }
+            }
+        }
+        names
+    }
+}
+
+pub struct ModuleGraph {
+    pub modules:Vec<Module>,
+}
+
+impl ModuleGraph {
+    //the file passed to ModuleLoader::load
+    pub fn root(&self) -> &Module {
+        &self.modules[0]
+    }
+    pub fn module(&self,path:&str) -> Option<&Module> {
+        self.modules.iter().find(|m| m.path == path)
+    }
+    //the module a span points into
+    pub fn file(&self,span:Span) -> Option<&Module> {
+        self.modules.get(span.file_id)
+    }
+    pub fn imports(&self,module:&Module) -> Vec<&Module> {
+        module.imports.iter().map(|i| &self.modules[*i]).collect()
+    }
+}
+
+//parses a file and everything it imports, each file exactly once
+#[derive(Default)]
+pub struct ModuleLoader {
+    modules:Vec<Option<Module>>,
+    loaded:HashMap<PathBuf,usize>,
+    stack:Vec<(PathBuf,String)>,
+}
+
+impl ModuleLoader {
+    pub fn new() -> ModuleLoader {
+        Self::default()
+    }
+
+    pub fn load(mut self,path:&str) -> Result<ModuleGraph,KzError> {
+        self.load_module(path,path,Span::default())?;
+        Ok(ModuleGraph {
+            modules:self.modules.into_iter().flatten().collect()
+        })
+    }
+
+    //span is the import statement in from that asked for path
+    fn load_module(&mut self,path:&str,from:&str,span:Span) -> Result<usize,KzError> {
+        let key = match fs::canonicalize(path) {
+            Ok(key) => key,
+            Err(_) => return Err(KzError::at(from,span,KzErr::Import(path.to_string())))
+        };
+        if let Some(start) = self.stack.iter().position(|(p,_)| *p == key) {
+            let mut chain:Vec<String> = self.stack[start..].iter().map(|(_,p)| p.clone()).collect();
+            chain.push(path.to_string());
+            return Err(KzError::at(from,span,KzErr::ImportCycle(chain)))
+        }
+        if let Some(index) = self.loaded.get(&key) {
+            return Ok(*index)
+        }
+        let mut f = match PaserFile::open(path) {
+            Ok(f) => f,
+            Err(_) => return Err(KzError::at(from,span,KzErr::Import(path.to_string())))
+        };
+        let index = self.modules.len();
+        f.id = index;
+        let statements = Parser::new(Lexer::new(f)).program()?;
+
+        self.modules.push(None);
+        self.loaded.insert(key.clone(),index);
+        self.stack.push((key,path.to_string()));
+        let mut imports = Vec::new();
+        for stem in &statements {
+            if let StatementKind::Import(import) = &stem.kind {
+                let child = self.load_module(&resolve(path,import),path,stem.span)?;
+                self.check_names(child,import,path,stem.span)?;
+                imports.push(child);
+            }
+        }
+        self.stack.pop();
+        self.modules[index] = Some(Module {
+            path:path.to_string(),
+            file_id:index,
+            statements,
+            imports,
+        });
+        Ok(index)
+    }
+
+    fn check_names(&self,child:usize,import:&ImportStatement,from:&str,span:Span) -> Result<(),KzError> {
+        if let Some(module) = &self.modules[child] {
+            let exports = module.exports();
+            for name in &import.names {
+                if !exports.contains(&name.as_str()) {
+                    return Err(KzError::at(from,span,KzErr::ImportName(name.clone(),module.path.clone())))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+//import paths are relative to the directory of the importing file
+fn resolve(from:&str,import:&ImportStatement) -> String {
+    let dir = Path::new(from).parent().unwrap_or(Path::new(""));
+    dir.join(import.file()).to_string_lossy().to_string()
+}
+
+#[test]
+fn test_module_graph() {
+    let graph = ModuleLoader::new().load("./src/script/module/main.kz").unwrap();
+    assert_eq!(graph.modules.len(),3);
+    let root = graph.root();
+    let imports:Vec<&str> = graph.imports(root).iter().map(|m| m.path.as_str()).collect();
+    assert_eq!(imports,vec!["./src/script/module/lib/math.kz","./src/script/module/util.kz"]);
+    let math = graph.module("./src/script/module/lib/math.kz").unwrap();
+    assert_eq!(math.exports(),vec!["sqrt","pow","E"]);
+    assert_eq!(graph.file(math.statements[1].span).unwrap().path,math.path);
+    //util imports lib/math.kz again, it is parsed only once
+    let util = graph.module("./src/script/module/util.kz").unwrap();
+    assert_eq!(graph.imports(util)[0].path,"./src/script/module/lib/math.kz");
+}
+
+#[test]
+fn test_module_cycle() {
+    let err = ModuleLoader::new().load("./src/script/module/cycle_a.kz").err().unwrap();
+    assert_eq!(err.err(),&KzErr::ImportCycle(vec![
+        "./src/script/module/cycle_a.kz".to_string(),
+        "./src/script/module/cycle_b.kz".to_string(),
+        "./src/script/module/cycle_a.kz".to_string(),
+    ]));
+}
+
+#[test]
+fn test_module_missing_name() {
+    let err = ModuleLoader::new().load("./src/script/module/bad_name.kz").err().unwrap();
+    assert_eq!(err.err(),&KzErr::ImportName("cbrt".to_string(),"./src/script/module/lib/math.kz".to_string()));
+}
@@ -0,0 +1,1389 @@
+use crate::ast::for_::ForStatement;
+use crate::ast::fun::{FunExpression};
+use crate::ast::ifs::IfStatement;
+use crate::ast::import::ImportStatement;
+use crate::ast::infix::InfixExpression;
+use crate::ast::lets::LetStatement;
+use crate::ast::param::ParamExpression;
+use crate::ast::type_::TypeStatement;
+use crate::parser::file::PaserFile;
+use crate::parser::lexer::Lexer;
+use crate::parser::span::Span;
+use crate::parser::token::{Operation, Token};
+use crate::ast::node::{Expression, ExpressionKind, Statement, StatementKind};
+use crate::parser::error::{KzErr, KzError};
+
+
+
+pub struct Parser {
+    lex:Lexer,
+    current_tok:Token,
+    peek_tok:Token,
+    current_span:Span,
+    peek_span:Span,
+    //the token before current_tok
+    prev_span:Span,
+    //consecutive /// lines waiting for the let, fun or type below them
+    doc:Vec<String>,
+    doc_line:i32,
+    errors:Vec<KzError>,
+}
+
+impl Parser {
+    pub(crate) fn new(l:Lexer) -> Parser {
+       let mut p =  Self {
+            lex:l,
+            current_tok:Token::Err("parsing not started".to_string()),
+            peek_tok:Token::Err("parsing not started".to_string()),
+            current_span:Span::default(),
+            peek_span:Span::default(),
+            prev_span:Span::default(),
+            doc:vec![],
+            doc_line:0,
+            errors:vec![],
+        };
+       p.next_token();
+       p.next_token();
+       p
+    }
+    fn current_token_is(&self, tok:Token) -> bool{
+        self.current_tok == tok
+    }
+    fn peek_token_is(&self, tok:Token) -> bool {
+        self.peek_tok == tok
+    }
+    fn expect_peek_token(&mut self, tok: Token) -> bool {
+        if self.peek_token_is(tok) {
+            self.next_token();
+            return true
+        }
+        false
+    }
+    fn expect_curr_token(&mut self, tok: Token) -> bool{
+        if self.current_token_is(tok) {
+            self.next_token();
+            return true
+        }
+        false
+    }
+    fn next_token(&mut self) {
+        self.prev_span = self.current_span;
+        self.current_tok = self.peek_tok.clone();
+        self.current_span = self.peek_span;
+        self.peek_tok = self.lex.next();
+        self.peek_span = self.lex.span();
+        self.notes()
+    }
+    //from start to the current token, which ends most nodes
+    fn span(&self,start:Span) -> Span {
+        start.to(self.current_span)
+    }
+    //from start to the token before the current one, for nodes closed by dump_token
+    fn span_prev(&self,start:Span) -> Span {
+        start.to(self.prev_span)
+    }
+    //notes never reach current_tok or peek_tok, doc notes are kept for take_doc
+    fn notes(&mut self) {
+        loop {
+            match &self.peek_tok {
+                Token::Notes => {},
+                Token::NotesBlock => {
+                    if self.lex.take_unclosed() {
+                        let err = KzError::at(self.lex.file_path(),self.peek_span,KzErr::Eof("'*/'".to_string()));
+                        self.errors.push(err);
+                    }
+                },
+                Token::DocNotes(text) => {
+                    if self.doc_line + 1 != self.peek_span.line {
+                        self.doc.clear();
+                    }
+                    self.doc.push(text.clone());
+                    self.doc_line = self.peek_span.line;
+                },
+                _ => break
+            }
+            self.peek_tok = self.lex.next();
+            self.peek_span = self.lex.span();
+        }
+    }
+    //the doc notes on the lines right above the current token
+    fn take_doc(&mut self) -> Option<String> {
+        let doc = std::mem::take(&mut self.doc);
+        if doc.is_empty() || self.doc_line + 1 != self.current_span.line {
+            return None
+        }
+        Some(doc.join("\n"))
+    }
+    //a single expression such as `user.age > 18 && region == "cn"`,
+    //spans in the result and in errors are offsets into source
+    pub fn parse_expression(source:&str) -> Result<Expression,KzError> {
+        let mut p = Parser::snippet(source);
+        let exp = p.expression(Operation::Lowest)?;
+        p.snippet_end(exp.span.end)?;
+        Ok(exp)
+    }
+    //a single type such as `[]?string` or `(int,error)`
+    pub fn parse_type(source:&str) -> Result<Token,KzError> {
+        let mut p = Parser::snippet(source);
+        let start = p.current_span;
+        let tok = p.type_token()?;
+        p.snippet_end(p.span(start).end)?;
+        Ok(tok)
+    }
+    fn snippet(source:&str) -> Parser {
+        let mut p = Parser::new(Lexer::new(PaserFile::from_bytes("<snippet>",source.as_bytes().to_vec())));
+        while p.current_token_is(Token::N) {
+            p.next_token();
+        }
+        p
+    }
+    //only newlines may follow a snippet ending at end
+    fn snippet_end(&mut self,end:usize) -> Result<(),KzError> {
+        if self.current_span.start < end {
+            self.next_token();
+        }
+        while self.current_token_is(Token::N) {
+            self.next_token();
+        }
+        if !self.errors.is_empty() {
+            return Err(self.errors.remove(0))
+        }
+        if !self.current_token_is(Token::Eof) {
+            return Err(self.error(KzErr::UnExpSymbol(self.current_tok.clone())))
+        }
+        Ok(())
+    }
+    //the whole input, failing with the first syntax error
+    pub(crate) fn program(&mut self) -> Result<Vec<Statement>,KzError> {
+        let (stems,mut errors) = self.program_recover();
+        if !errors.is_empty() {
+            return Err(errors.remove(0))
+        }
+        Ok(stems)
+    }
+    //the whole input, every syntax error is collected and its statement
+    //is kept as StatementKind::Error so the rest of the file is still parsed
+    pub(crate) fn program_recover(&mut self) -> (Vec<Statement>,Vec<KzError>) {
+        let mut v = self.block();
+        while self.current_token_is(Token::RightCurlyBracket) {
+            self.errors.push(self.error(KzErr::UnExpSymbol(Token::RightCurlyBracket)));
+            self.next_token();
+            v.extend(self.block());
+        }
+        (v,std::mem::take(&mut self.errors))
+    }
+    //statements up to the closing `}` or eof, which is left as the current token
+    fn block(&mut self) -> Vec<Statement> {
+        let mut v = Vec::new();
+        loop {
+            match self.current_tok {
+                Token::N | Token::Sem => self.next_token(),
+                Token::Eof | Token::RightCurlyBracket => break,
+                _ => {
+                    let start = self.current_span;
+                    match self.parser_statement() {
+                        Ok(stem) => v.push(stem),
+                        Err(e) => {
+                            self.errors.push(e);
+                            self.synchronize();
+                            v.push(Statement::new(StatementKind::Error,self.span_prev(start)));
+                        }
+                    }
+                }
+            }
+        }
+        v
+    }
+    //panic mode: skip the rest of a broken statement, nested {} included,
+    //stopping in front of the `}` that closes the enclosing block
+    fn synchronize(&mut self) {
+        let mut depth = 0;
+        loop {
+            match self.current_tok {
+                Token::Eof => return,
+                Token::LeftCurlyBracket => depth += 1,
+                Token::RightCurlyBracket => {
+                    if depth == 0 {
+                        return
+                    }
+                    depth -= 1;
+                    if depth == 0 {
+                        self.next_token();
+                        return
+                    }
+                },
+                Token::N | Token::Sem if depth == 0 => {
+                    self.next_token();
+                    return
+                },
+                _ => {}
+            }
+            self.next_token();
+        }
+    }
+    fn parser_statement(&mut self) -> Result<Statement,KzError> {
+        match self.current_tok {
+            Token::Let => self.let_statement(),
+            Token::Const => self.const_statement(),
+            Token::If => self.if_statement(),
+            Token::Fun => {
+                let start = self.current_span;
+                let doc = self.take_doc();
+                let mut f = self.fun_expression()?;
+                f.doc = doc;
+                Ok(Statement::new(StatementKind::Fun(f),self.span_prev(start)))
+            },
+            Token::Type => self.type_statement(),
+            Token::Ident(_) => self.ident_statement(),
+            Token::SelfSub | Token::SelfAdd => self.prefix_statement(),
+            Token::For => self.for_statement(),
+            Token::Return | Token::Break | Token::Continue => self.rbc_statement(),
+            Token::Import => self.import_statement(),
+            Token::Pub => self.pub_statement(),
+            Token::Defer => self.defer_statement(),
+            _ => {
+                Err(self.error(KzErr::Program(self.current_tok.clone())))
+            }
+        }
+    }
+    fn ident(&self) -> Option<String>{
+       match &self.peek_tok {
+           Token::Ident(name) => Some(name.to_string()),
+           _ => None
+       }
+    }
+    fn let_statement(&mut self) -> Result<Statement,KzError> {
+        let start = self.current_span;
+        let doc = self.take_doc();
+        let mut let_stem = if let Some(ident) = self.ident() {
+            self.next_token();
+            LetStatement::new(ident)
+        } else if self.expect_peek_token(Token::LeftBracket) {
+            LetStatement::new_tuple(self.name_list(Token::RightBracket)?)
+        } else {
+            return Err(self.error(KzErr::ExpectedName))
+        };
+        if self.expect_peek_token(Token::Colon) {
+            self.next_token();
+            let_stem.token_type = self.type_token()?;
+        }
+        //let x:int  without a value is assigned further down, see analysis::dataflow
+        let end = [Token::N,Token::Sem,Token::Eof,Token::RightCurlyBracket];
+        if self.expect_peek_token(Token::Assign) {
+            self.next_token();
+            let_stem.exp = Some(self.expression(Operation::Lowest)?);
+        } else if !end.iter().any(|tok| self.peek_token_is(tok.clone())) {
+            return Err(self.expected(Token::Assign))
+        }
+        let_stem.doc = doc;
+        let span = self.span(start);
+        self.dump_boundary(span.end);
+        Ok(Statement::new(StatementKind::Let(let_stem),span))
+    }
+    fn const_statement(&mut self) -> Result<Statement,KzError> {
+        let stem = self.let_statement()?;
+        match stem.kind {
+            //only a let is assigned later, a const has its value where it is declared
+            StatementKind::Let(c) if !c.is_tuple() && c.exp.is_none() => {
+                Err(KzError::at(self.lex.file_path(),stem.span,KzErr::ConstExpr(c.ident)))
+            },
+            StatementKind::Let(c) if !c.is_tuple() => Ok(Statement::new(StatementKind::Const(c),stem.span)),
+            _ => Err(self.error(KzErr::ExpectedName))
+        }
+    }
+    //let (a,b)  import math.{a,b}
+    fn name_list(&mut self,end:Token) -> Result<Vec<String>,KzError> {
+        let mut names = Vec::new();
+        while !self.current_token_is(end.clone()) {
+            if self.peek_token_is(Token::Eof) {
+                return Err(self.expected(end))
+            }
+            match self.ident() {
+                Some(name) => names.push(name),
+                None => return Err(self.error(KzErr::ExpectedName))
+            }
+            self.next_token();
+            if !self.expect_peek_token(Token::Comma) && !self.expect_peek_token(end.clone()) {
+                return Err(self.expected(end))
+            }
+        }
+        Ok(names)
+    }
+    //import "lib/math.kz"  import math.{sqrt,pow}
+    fn import_statement(&mut self) -> Result<Statement,KzError> {
+        let start = self.current_span;
+        self.next_token();
+        let mut import = match self.current_tok.clone() {
+            Token::Basics(path,tok) if *tok == Token::String => ImportStatement::new(path,false),
+            Token::Ident(mut path) => {
+                while self.expect_peek_token(Token::Dot) {
+                    match self.ident() {
+                        Some(name) => {
+                            path = format!("{}.{}",path,name);
+                            self.next_token();
+                        },
+                        None => break
+                    }
+                }
+                ImportStatement::new(path,true)
+            },
+            _ => return Err(self.error(KzErr::UnExpSymbol(self.current_tok.clone())))
+        };
+        let mut span = self.span(start);
+        if import.module && self.current_token_is(Token::Dot) {
+            if !self.expect_peek_token(Token::LeftCurlyBracket) {
+                return Err(self.expected(Token::LeftCurlyBracket))
+            }
+            import.names = self.name_list(Token::RightCurlyBracket)?;
+            span = self.span(start);
+            self.next_token();
+        }
+        self.dump_boundary(span.end);
+        Ok(Statement::new(StatementKind::Import(import),span))
+    }
+    //defer close(f)  defer { ... }
+    fn defer_statement(&mut self) -> Result<Statement,KzError> {
+        let start = self.current_span;
+        self.next_token();
+        if self.current_token_is(Token::LeftCurlyBracket) {
+            self.next_token();
+            let body = self.block();
+            self.dump_token(Token::RightCurlyBracket)?;
+            return Ok(Statement::new(StatementKind::Defer(body),self.span_prev(start)))
+        }
+        let exp = self.expression(Operation::Lowest)?;
+        let span = exp.span;
+        let invoke = Statement::new(StatementKind::Invoke(exp),span);
+        let span = self.span(start);
+        self.dump_boundary(span.end);
+        Ok(Statement::new(StatementKind::Defer(vec![invoke]),span))
+    }
+    //pub fun / pub type / pub const
+    fn pub_statement(&mut self) -> Result<Statement,KzError> {
+        let start = self.current_span;
+        self.next_token();
+        match self.current_tok {
+            Token::Fun | Token::Type | Token::Const => {
+                let stem = self.parser_statement()?;
+                let span = start.to(stem.span);
+                Ok(Statement::new(StatementKind::Pub(Box::new(stem)),span))
+            },
+            _ => Err(self.error(KzErr::UnExpSymbol(self.current_tok.clone())))
+        }
+    }
+    fn list_value_expression(&mut self) -> Result<Expression,KzError>{
+        let start = self.current_span;
+        self.expect_curr_token(Token::LeftSquareBra); //peek [
+        let mut list:Vec<Expression> = Vec::new();
+
+        while !self.current_token_is(Token::RightSquareBra) {
+            list.push(self.expression(Operation::Lowest)?);
+            if !self.expect_peek_token(Token::Comma) && !self.peek_token_is(Token::RightSquareBra) {
+                return Err(self.expected(Token::RightSquareBra))
+            }
+            self.next_token();
+        }
+        Ok(Expression::new(ExpressionKind::ListValue(list),self.span(start)))
+    }
+    fn expression(&mut self,op:Operation) -> Result<Expression,KzError> {
+        let start = self.current_span;
+        let mut left = match &self.current_tok {
+            Token::Basics(val,tok) => Expression::new(self.basics(val,tok.as_ref())?,start),
+            Token::Ident(val) => Expression::new(ExpressionKind::Ident(val.clone()),start),
+            Token::LeftSquareBra => self.list_value_expression()?,
+            Token::LeftBracket => self.group_expression()?,
+            Token::Pipe => {
+                self.next_token();
+                let params = self.lambda_params(Token::Pipe)?;
+                self.lambda_expression(params,start)?
+            },
+            //|| x, a lambda without parameters
+            Token::Or => self.lambda_expression(vec![],start)?,
+            Token::Nil => Expression::new(ExpressionKind::Nil,start),
+            Token::Fun => {
+               let exp = self.fun_expression()?;
+               Expression::new(ExpressionKind::Fun(Box::new(exp)),self.span_prev(start))
+            },
+            _ => {
+                if self.current_token_is(Token::Eof) {
+                    return Err(self.error(KzErr::Eof("an expression".to_string())))
+                }
+                return Err( self.error(KzErr::UnExpSymbol(self.current_tok.clone())))
+            }
+        };
+        while op < self.peek_operation() && !self.peek_stem_end() {
+
+             left = match &self.peek_tok {
+                 Token::Add | Token::Sub |
+                 Token::Div | Token::Mul |
+                 Token::LT | Token::GT | Token::LTEqu | Token::GTEqu |
+                 Token::BangEqu | Token::Equ | Token::And | Token::Or
+                 => {
+                     self.next_token();
+                     self.infix_expression(left)?
+                 },
+                 Token::LeftBracket => {
+                     self.next_token();
+                     self.call_expression(left)?
+                 },
+                 Token::LeftSquareBra => {
+                     self.next_token();
+                     self.index_expression(left)?
+                 },
+                 Token::SelfAdd | Token::SelfSub => {
+                     self.next_token();
+                     self.self_operation_expression(left,false)?
+                 },
+                 Token::Dot => {
+                     self.next_token();
+                     self.field_expression(left)?
+                 },
+                 Token::Question => {
+                     self.next_token();
+                     let span = left.span.to(self.current_span);
+                     Expression::new(ExpressionKind::Try(Box::new(left)),span)
+                 },
+                 _ => {return Err(self.error(KzErr::UnOpSymbol(self.peek_tok.clone())))}
+             };
+        }
+        Ok(left)
+    }
+    //(a + b)  tuple (a,b)  lambda (a,b) => a + b
+    fn group_expression(&mut self) -> Result<Expression,KzError> {
+        let start = self.current_span;
+        self.next_token();
+        if self.current_token_is(Token::RightBracket) {
+            if self.expect_peek_token(Token::FatArrow) {
+                return self.lambda_expression(vec![],start)
+            }
+            return Ok(Expression::new(ExpressionKind::Tuple(vec![]),self.span(start)))
+        }
+        if matches!(self.current_tok,Token::Ident(_)) && self.peek_token_is(Token::Colon) {
+            let params = self.lambda_params(Token::RightBracket)?;
+            if !self.expect_peek_token(Token::FatArrow) {
+                return Err(self.expected(Token::FatArrow))
+            }
+            return self.lambda_expression(params,start)
+        }
+        let mut list = vec![self.expression(Operation::Lowest)?];
+        let tuple = self.peek_token_is(Token::Comma);
+        while self.expect_peek_token(Token::Comma) {
+            self.next_token();
+            list.push(self.expression(Operation::Lowest)?);
+        }
+        if !self.expect_peek_token(Token::RightBracket) {
+            return Err(self.expected(Token::RightBracket))
+        }
+        if self.expect_peek_token(Token::FatArrow) {
+            let mut params = Vec::new();
+            for exp in list {
+                match exp.kind {
+                    ExpressionKind::Ident(name) => {
+                        let param = ExpressionKind::Param(Box::new(ParamExpression::new(name,Token::Unknown)));
+                        params.push(Expression::new(param,exp.span))
+                    },
+                    _ => return Err(self.error(KzErr::ParamName))
+                }
+            }
+            return self.lambda_expression(params,start)
+        }
+        if !tuple {
+            //(a + b) covers its brackets
+            let mut exp = list.remove(0);
+            exp.span = self.span(start);
+            return Ok(exp)
+        }
+        Ok(Expression::new(ExpressionKind::Tuple(list),self.span(start)))
+    }
+    //x, y:int  up to | or )
+    fn lambda_params(&mut self,end:Token) -> Result<Vec<Expression>,KzError> {
+        let mut v = Vec::new();
+        while !self.current_token_is(end.clone()) {
+            if self.current_token_is(Token::Eof) {
+                return Err(self.expected(end))
+            }
+            let start = self.current_span;
+            let name = match &self.current_tok {
+                Token::Ident(name) => name.clone(),
+                _ => return Err(self.error(KzErr::ParamName))
+            };
+            let mut tok = Token::Unknown;
+            if self.expect_peek_token(Token::Colon) {
+                self.next_token();
+                tok = self.type_token()?;
+            }
+            let param = ExpressionKind::Param(Box::new(ParamExpression::new(name,tok)));
+            v.push(Expression::new(param,self.span(start)));
+            if self.expect_peek_token(Token::Comma) {
+                self.next_token();
+            } else if !self.expect_peek_token(end.clone()) {
+                return Err(self.expected(end))
+            }
+        }
+        Ok(v)
+    }
+    //body of |x| x * 2 / (x) => x * 2, either an expression or a block
+    fn lambda_expression(&mut self,params:Vec<Expression>,start:Span) -> Result<Expression,KzError> {
+        let mut fn_exp = FunExpression::new();
+        fn_exp.lambda = true;
+        fn_exp.param_number = params.len();
+        fn_exp.param_exp = Some(params);
+        self.next_token();
+        if self.current_token_is(Token::LeftCurlyBracket) {
+            self.next_token();
+            fn_exp.body = self.block();
+            self.dump_token(Token::RightCurlyBracket)?;
+            fn_exp.ret_type = fn_exp.infer_ret_type();
+            return Ok(Expression::new(ExpressionKind::Fun(Box::new(fn_exp)),self.span_prev(start)))
+        }
+        let exp = self.expression(Operation::Lowest)?;
+        let span = exp.span;
+        fn_exp.body = vec![Statement::new(StatementKind::Return(Some(exp)),span)];
+        fn_exp.ret_type = fn_exp.infer_ret_type();
+        Ok(Expression::new(ExpressionKind::Fun(Box::new(fn_exp)),self.span(start)))
+    }
+    fn infix_expression(&mut self,left:Expression) -> Result<Expression,KzError> {
+        let mut exp = InfixExpression::new(left);
+        exp.op_symbol = self.current_tok.clone();
+        let op = self.curr_operation();
+        self.next_token();
+        exp.right = self.expression(op)?;
+        let span = exp.left.span.to(exp.right.span);
+        Ok(Expression::new(ExpressionKind::Infix(Box::new(exp)),span))
+    }
+    fn basics(&self, val:&str, tok: &Token) -> Result<ExpressionKind,KzError>{
+        let value = || self.error(KzErr::Value(val.to_string()));
+        if *tok == Token::Int {
+            return val.parse::<i32>().map(ExpressionKind::Int).map_err(|_| value())
+        }
+        if *tok == Token::Float {
+            //past the range of f32 parses as inf, which no tool can read back
+            return val.parse::<f32>().ok().filter(|val| val.is_finite()).map(ExpressionKind::Float).ok_or_else(value)
+        }
+        if *tok == Token::Bool {
+            return val.parse::<bool>().map(ExpressionKind::Bool).map_err(|_| value())
+        }
+        if *tok == Token::String {
+            return Ok(ExpressionKind::String(val.to_string()))
+        }
+
+        Err(self.error(KzErr::Value(val.to_string())))
+    }
+    fn error(&self, err:KzErr) -> KzError {
+        KzError::at(self.lex.file_path(),self.current_span,err)
+    }
+    //`tok` is missing, at the end of the input that is an unexpected eof
+    fn expected(&self, tok:Token) -> KzError {
+        if self.current_token_is(Token::Eof) || self.peek_token_is(Token::Eof) {
+            return self.error(KzErr::Eof(format!("'{:?}'",tok)))
+        }
+        self.error(KzErr::Expected(tok))
+    }
+
+    fn peek_operation(&self) -> Operation {
+        self.operation(&self.peek_tok)
+    }
+    fn curr_operation(&self) -> Operation {
+        self.operation(&self.current_tok)
+    }
+    fn operation(&self, tok: &Token) -> Operation {
+        match tok {
+            Token::Add | Token::Sub | Token::SelfSub | Token::SelfAdd => Operation::AddAndSub,
+            Token::Div | Token::Mul => Operation::MulAndDiv,
+            Token::LT | Token::GT  | Token::LTEqu | Token::GTEqu => Operation::LtAndGt,
+            Token::Equ | Token::BangEqu => Operation::EquAls,
+            Token::And => Operation::And,
+            Token::Or => Operation::Or,
+            Token::LeftBracket | Token::Question | Token::Dot => Operation::Call,
+            Token::LeftSquareBra => Operation::Index,
+            _ => Operation::Lowest
+        }
+    }
+    fn peek_stem_end(&self) -> bool {
+        self.stem_end(&self.peek_tok)
+    }
+    fn stem_end(&self,tok: &Token) -> bool {
+        matches!(tok,Token::N | Token::Sem | Token::Eof)
+    }
+
+    //if statement
+    fn if_statement(&mut self) -> Result<Statement,KzError>{
+        let start = self.current_span;
+        self.next_token();
+        let mut binding = None;
+        if self.current_token_is(Token::Let) {
+            //if let x = maybe
+            binding = self.ident();
+            if binding.is_none() {
+                return Err(self.error(KzErr::ExpectedName))
+            }
+            self.next_token();
+            if !self.expect_peek_token(Token::Assign) {
+                return Err(self.expected(Token::Assign))
+            }
+            self.next_token();
+        }
+        let exp = self.expression(Operation::Lowest)?;
+        let mut if_stem = IfStatement::new(exp);
+        if_stem.binding = binding;
+        if !self.expect_peek_token(Token::LeftCurlyBracket) {
+            return Err(self.expected(Token::LeftCurlyBracket))
+        }
+        self.next_token();
+
+        if_stem.consequence = self.block();
+        if self.expect_peek_token(Token::Else) {
+            self.next_token();
+            if self.current_token_is(Token::If) {
+                //else if
+                let stem = self.if_statement()?;
+                let span = start.to(stem.span);
+                if_stem.alternative = vec![stem];
+                return Ok(Statement::new(StatementKind::IF(if_stem),span))
+            }
+            if !self.expect_curr_token(Token::LeftCurlyBracket) {
+                return Err(self.expected(Token::LeftCurlyBracket))
+            }
+            if_stem.alternative = self.block();
+        }
+        self.dump_token(Token::RightCurlyBracket)?;
+        Ok(Statement::new(StatementKind::IF(if_stem),self.span_prev(start)))
+    }
+    //fn
+    fn fun_head_expression(&mut self,need_param_name:bool) -> Result<FunExpression,KzError> {
+        let name = match &self.peek_tok {
+            Token::Ident(name) => {
+                Some(name.clone())
+            } ,
+            _ => { None }
+        };
+        let mut fn_exp = FunExpression::new();
+        self.next_token();
+        if let Some(name) = name {
+            if !self.expect_peek_token(Token::LeftBracket) {
+                return Err(self.expected(Token::LeftBracket))
+            }
+            fn_exp.name = Some(Expression::new(ExpressionKind::Ident(name),self.prev_span));
+        }
+        self.next_token();
+        fn_exp.param_exp = Some(self.param_expression(Token::RightBracket,false,need_param_name)?);
+        fn_exp.param_number = fn_exp.param_exp.as_ref().unwrap().len();
+        self.expect_peek_token(Token::Arrow);
+        if self.peek_type() {
+            self.next_token();
+            fn_exp.ret_type = Some(self.type_token()?);
+        }
+        Ok(fn_exp)
+    }
+    //fun expression
+    fn fun_expression(&mut self) -> Result<FunExpression,KzError> {
+        let mut fn_exp = self.fun_head_expression(true)?;
+        if !self.expect_peek_token(Token::LeftCurlyBracket) {
+            return Err(self.expected(Token::LeftCurlyBracket))
+        }
+        self.next_token();
+        fn_exp.body = self.block();
+
+        self.dump_token(Token::RightCurlyBracket)?;
+        Ok(fn_exp)
+    }
+
+    fn param_expression(&mut self, tok: Token,n:bool,need_name:bool) -> Result<Vec<Expression>,KzError>{
+        let mut v = Vec::<Expression>::new();
+        while !self.current_token_is(tok.clone()) {
+            if self.current_token_is(Token::Eof) {
+                return Err(self.expected(tok))
+            }
+            if self.current_tok == Token::N && n {
+                self.next_token();
+                continue
+            }
+            let start = self.current_span;
+            let variadic = self.expect_curr_token(Token::Ellipsis);
+            let name = match &self.current_tok {
+                Token::Ident(val) => Some(val.clone()),
+                _ => { None }
+            };
+            if let Some(name) = name {
+                if !self.expect_peek_token(Token::Colon)  {
+                    return Err( self.expected(Token::Colon))
+                }
+                self.next_token();
+                let mut param = ParamExpression::new(name,self.type_token()?);
+                param.variadic = variadic;
+                if self.expect_peek_token(Token::Assign) {
+                    self.next_token();
+                    param.default = Some(self.expression(Operation::Lowest)?);
+                }
+                v.push(Expression::new(ExpressionKind::Param(Box::new(param)),self.span(start)));
+
+                self.expect_peek_token(Token::Comma);
+                self.next_token()
+            } else {
+                if !need_name {
+                    //不需要名称的情况下
+                    let mut param = ParamExpression::new("".to_string(),self.type_token()?);
+                    param.variadic = variadic;
+                    v.push(Expression::new(ExpressionKind::Param(Box::new(param)),self.span(start)));
+                    self.expect_peek_token(Token::Comma);
+                    self.next_token()
+                } else {
+                    return Err(self.error(KzErr::ParamName))
+                }
+            }
+
+        }
+        Ok(v)
+    }
+    fn type_statement(&mut self) -> Result<Statement,KzError> {
+        //type
+        let start = self.current_span;
+        let doc = self.take_doc();
+        self.next_token();
+        if let Token::Ident(name) = self.current_tok.clone(){
+            if self.expect_peek_token(Token::Struct) {
+                let mut stem = TypeStatement::new(name,self.struct_expression()?);
+                stem.doc = doc;
+                let span = start.to(stem.exp.span);
+                return Ok(Statement::new(StatementKind::Type(stem),span));
+            }
+            if self.expect_peek_token(Token::Fun) {
+                let mut stem = TypeStatement::new(name,self.type_fn_expression()?);
+                stem.doc = doc;
+                let span = start.to(stem.exp.span);
+                return Ok(Statement::new(StatementKind::Type(stem),span));
+            }
+            if self.peek_type() {
+                self.next_token();
+                let type_start = self.current_span;
+                let exp = Expression::new(ExpressionKind::Basic(self.type_token()?),self.span(type_start));
+                let mut stem = TypeStatement::new(name,exp);
+                stem.doc = doc;
+                let stem = Statement::new(StatementKind::Type(stem),self.span(start));
+                self.next_token();
+                return Ok(stem)
+            }
+        }
+        Err( self.error(KzErr::ExpectedName))
+    }
+    fn struct_expression(&mut self) -> Result<Expression,KzError> {
+        //struct 开始
+        let start = self.current_span;
+        if !self.expect_peek_token(Token::LeftCurlyBracket) {
+            return Err(self.expected(Token::LeftCurlyBracket))
+        }
+        self.dump_n();//清除换行
+        self.next_token();
+        let param = self.param_expression(Token::RightCurlyBracket, true,true)?;
+
+        self.dump_token(Token::RightCurlyBracket)?;
+        Ok(Expression::new(ExpressionKind::Struct(param),self.span_prev(start)))
+    }
+    fn type_fn_expression(&mut self) -> Result<Expression,KzError> {
+        //fun
+        let start = self.current_span;
+        let f = self.fun_head_expression(false)?;
+        let span = self.span(start);
+        if !self.current_token_is(Token::N) {
+            self.next_token();
+        }
+        Ok(Expression::new(ExpressionKind::FunType(f.param_exp,f.ret_type),span))
+    }
+    fn dump_n(&mut self) {
+        while self.expect_peek_token(Token::N) {
+        }
+    }
+    fn dump_token(&mut self,tok:Token) -> Result<(),KzError> {
+        loop {
+            if self.current_token_is(tok.clone()) {
+                self.next_token();
+                return Ok(())
+            }
+            if self.current_token_is(Token::Eof) {
+                return Err(self.expected(tok))
+            }
+            self.next_token();
+        }
+    }
+    //up to the end of a statement, which ends at byte end. the first token left over after
+    //it is reported, `let a = 1 2`, and so is every token the lexer could not read
+    fn dump_boundary(&mut self,end:usize){
+        let mut reported = false;
+        loop {
+            if self.current_token_is(Token::N) || self.current_token_is(Token::Sem)
+                || self.current_token_is(Token::Eof) || self.current_token_is(Token::LeftCurlyBracket)
+                || self.current_token_is(Token::RightCurlyBracket)
+            {
+                 break
+            }
+            if self.current_span.start >= end && (!reported || matches!(self.current_tok,Token::Err(_))) {
+                self.errors.push(self.error(KzErr::UnExpSymbol(self.current_tok.clone())));
+                reported = true;
+            }
+            self.next_token();
+        }
+    }
+    //int []int (int,string)
+    fn type_token(&mut self) -> Result<Token,KzError> {
+        if self.current_token_is(Token::Question) {
+            self.next_token();
+            return Ok(Token::Optional(Box::new(self.type_token()?)))
+        }
+        if self.current_token_is(Token::LeftBracket) {
+            return self.tuple_token()
+        }
+        if self.current_token_is(Token::LeftSquareBra) {
+            return self.array_token()
+        }
+        if !Token::let_type(&self.current_tok) {
+            return Err(self.error(KzErr::Type(self.current_tok.clone())))
+        }
+        Ok(self.current_tok.clone())
+    }
+    fn peek_type(&self) -> bool {
+        Token::let_type(&self.peek_tok) || self.peek_token_is(Token::LeftSquareBra)
+            || self.peek_token_is(Token::LeftBracket) || self.peek_token_is(Token::Question)
+    }
+    fn tuple_token(&mut self) -> Result<Token,KzError> {
+        let mut v = Vec::new();
+        while !self.current_token_is(Token::RightBracket) {
+            if self.peek_token_is(Token::Eof) {
+                return Err(self.expected(Token::RightBracket))
+            }
+            self.next_token();
+            v.push(self.type_token()?);
+            if !self.expect_peek_token(Token::Comma) && !self.expect_peek_token(Token::RightBracket) {
+                return Err(self.expected(Token::RightBracket))
+            }
+        }
+        Ok(Token::Tuple(v))
+    }
+    fn array_token(&mut self) -> Result<Token,KzError> {
+       let number = match &self.peek_tok {
+           Token::Basics(val,tok) if *tok.as_ref() == Token::Int => {
+               val.parse::<i32>().map_err(|_| self.error(KzErr::Value(val.to_string())))?
+           },
+           _ => {0}
+       };
+       if !self.peek_token_is(Token::RightSquareBra) {
+           self.next_token();
+       }
+       if !self.expect_peek_token(Token::RightSquareBra) {
+           return Err(self.expected(Token::RightSquareBra))
+       }
+       self.next_token();
+       Ok(Token::Array(number,Box::new(self.type_token()?)))
+    }
+    fn ident_statement(&mut self) -> Result<Statement,KzError>{
+        let exp = self.expression(Operation::Lowest)?;
+        if self.expect_peek_token(Token::Assign) {
+            self.next_token();
+            let value = self.expression(Operation::Lowest)?;
+            let span = exp.span.to(value.span);
+            self.dump_boundary(span.end);
+            return Ok(Statement::new(StatementKind::Assign(exp,value),span))
+        }
+        let span = exp.span;
+        self.dump_boundary(span.end);
+        Ok(Statement::new(StatementKind::Invoke(exp),span))
+    }
+    fn call_expression(&mut self,left:Expression) -> Result<Expression,KzError> {
+        self.next_token();//(
+        let mut list:Vec<Expression> = Vec::new();
+        while !self.current_token_is(Token::RightBracket) {
+            list.push(self.argument_expression()?);
+            if !self.expect_peek_token(Token::Comma) && !self.peek_token_is(Token::RightBracket) {
+                return Err(self.expected(Token::RightBracket))
+            }
+            self.next_token();
+        }
+        let span = left.span.to(self.current_span);
+        Ok(Expression::new(ExpressionKind::Call(Box::new(left),list),span))
+    }
+    //f(a, level: 3)
+    fn argument_expression(&mut self) -> Result<Expression,KzError> {
+        let start = self.current_span;
+        if let Token::Ident(name) = self.current_tok.clone() {
+            if self.expect_peek_token(Token::Colon) {
+                self.next_token();
+                let exp = self.expression(Operation::Lowest)?;
+                let span = start.to(exp.span);
+                return Ok(Expression::new(ExpressionKind::NamedArg(name,Box::new(exp)),span))
+            }
+        }
+        self.expression(Operation::Lowest)
+    }
+
+    fn index_expression(&mut self,left:Expression) -> Result<Expression,KzError> {
+        self.next_token(); // [
+        let exp = self.expression(Operation::Lowest)?;
+        if !self.expect_peek_token(Token::RightSquareBra) {
+            return Err(self.expected(Token::RightSquareBra))
+        }
+        let span = left.span.to(self.current_span);
+        Ok(Expression::new(ExpressionKind::Index(Box::new(left),Box::new(exp)),span))
+    }
+
+    //user.age
+    fn field_expression(&mut self,left:Expression) -> Result<Expression,KzError> {
+        let name = match self.ident() {
+            Some(name) => name,
+            None => return Err(self.error(KzErr::ExpectedName))
+        };
+        self.next_token();
+        let span = left.span.to(self.current_span);
+        Ok(Expression::new(ExpressionKind::Field(Box::new(left),name),span))
+    }
+    fn self_operation_expression(&mut self,name:Expression,left:bool) -> Result<Expression,KzError> {
+        //-- ++
+        let tok = self.current_tok.clone();
+        let span = name.span.to(self.current_span);
+        Ok(Expression::new(ExpressionKind::SelfOp(Box::new(name),tok,left),span))
+    }
+
+    fn prefix_statement(&mut self) -> Result<Statement,KzError> {
+        let start = self.current_span;
+        let tok = self.current_tok.clone();
+        self.next_token();
+        let exp = self.expression(Operation::Lowest)?;
+        let span = self.span(start);
+        self.next_token();
+        let exp = Expression::new(ExpressionKind::SelfOp(Box::new(exp),tok,true),span);
+        Ok(Statement::new(StatementKind::Invoke(exp),span))
+    }
+
+    fn for_statement(&mut self) -> Result<Statement,KzError> {
+        //for
+        let start = self.current_span;
+        self.next_token();
+        let start_condition = self.parser_statement()?;
+        if !self.expect_curr_token(Token::Sem) {
+            return Err(self.expected(Token::Sem))
+        }
+        let condition = self.expression(Operation::Lowest)?;
+        self.next_token();
+        if !self.expect_curr_token(Token::Sem) {
+            return Err(self.expected(Token::Sem))
+        }
+        let self_operation = self.parser_statement()?;
+        let mut for_stem = ForStatement::new(start_condition,condition,self_operation);
+        if !self.expect_curr_token(Token::LeftCurlyBracket) {
+            return Err(self.expected(Token::LeftCurlyBracket))
+        }
+        for_stem.consequence = self.block();
+        self.dump_token(Token::RightCurlyBracket)?;
+        Ok(Statement::new(StatementKind::For(for_stem),self.span_prev(start)))
+    }
+
+    //return break continue
+    fn rbc_statement(&mut self) -> Result<Statement,KzError> {
+        let start = self.current_span;
+        let tok = self.current_tok.clone();
+        self.next_token();
+        let stem = match tok {
+            //a bare return ends the line or the block
+            Token::Return if self.current_token_is(Token::N) || self.current_token_is(Token::Sem)
+                || self.current_token_is(Token::RightCurlyBracket) || self.current_token_is(Token::Eof) => {
+                Statement::new(StatementKind::Return(None),start)
+            },
+            Token::Return => {
+                let exp = self.expression(Operation::Lowest)?;
+                Statement::new(StatementKind::Return(Some(exp)),self.span(start))
+            },
+            Token::Break => Statement::new(StatementKind::Break,start),
+            Token::Continue => Statement::new(StatementKind::Continue,start),
+            _ => { return Err(self.error(KzErr::UnExpSymbol(tok)))}
+        };
+        self.dump_boundary(stem.span.end);
+        Ok(stem)
+    }
+}
+
+#[test]
+fn test_parser_011(){
+    let stem = parser_file("./src/script/11_parser.kz");
+    assert_eq!(stem[0].kind,StatementKind::Type(TypeStatement::new("pair".to_string(),ExpressionKind::Basic(Token::Tuple(vec![Token::Int,Token::String])).into())));
+    match &stem[1].kind {
+        StatementKind::Fun(f) => {
+            assert_eq!(f.ret_type,Some(Token::Tuple(vec![Token::Int,Token::Ident("error".to_string())])));
+            assert!(matches!(&f.body[0].kind,StatementKind::Return(Some(Expression { kind:ExpressionKind::Tuple(v), .. })) if v.len() == 2));
+        },
+        _ => panic!("expected fun")
+    }
+    match &stem[2].kind {
+        StatementKind::Let(l) => {
+            assert_eq!(l.names(),vec!["q","e"]);
+            assert!(matches!(l.exp,Some(Expression { kind:ExpressionKind::Call(_,_), .. })));
+        },
+        _ => panic!("expected let")
+    }
+    match &stem[3].kind {
+        StatementKind::Let(l) => {
+            assert_eq!(l.token_type,Token::Tuple(vec![Token::Int,Token::String]));
+            assert_eq!(l.exp,Some(ExpressionKind::Tuple(vec![ExpressionKind::Int(1).into(),ExpressionKind::String("a".to_string()).into()]).into()));
+        },
+        _ => panic!("expected let")
+    }
+    match &stem[4].kind {
+        StatementKind::Let(l) => {
+            assert!(matches!(&l.exp,Some(Expression { kind:ExpressionKind::Infix(exp), .. }) if exp.op_symbol == Token::Mul));
+        },
+        _ => panic!("expected let")
+    }
+}
+#[test]
+fn test_parser_012(){
+    let stem = parser_file("./src/script/12_parser.kz");
+    match &stem[0].kind {
+        StatementKind::Let(l) => {
+            assert!(l.is_optional());
+            assert_eq!(l.exp,Some(ExpressionKind::Nil.into()));
+        },
+        _ => panic!("expected let")
+    }
+    match &stem[1].kind {
+        StatementKind::Let(l) => assert!(!l.is_optional()),
+        _ => panic!("expected let")
+    }
+    match &stem[2].kind {
+        StatementKind::Fun(f) => assert_eq!(f.ret_type,Some(Token::Optional(Box::new(Token::String)))),
+        _ => panic!("expected fun")
+    }
+    match &stem[3].kind {
+        StatementKind::IF(i) => {
+            assert_eq!(i.binding,Some("x".to_string()));
+            assert_eq!(i.condition.kind,ExpressionKind::Ident("maybe".to_string()));
+            assert_eq!(i.consequence.len(),1);
+            assert_eq!(i.alternative.len(),1);
+        },
+        _ => panic!("expected if")
+    }
+}
+#[test]
+fn test_parser_013(){
+    let stem = parser_file("./src/script/13_parser.kz");
+    match &stem[0].kind {
+        StatementKind::Const(c) => {
+            assert_eq!(c.ident,"MAX");
+            assert_eq!(c.token_type,Token::Int);
+            assert_eq!(c.exp,Some(ExpressionKind::Int(100).into()));
+        },
+        _ => panic!("expected const")
+    }
+    assert_eq!(stem[5].kind,StatementKind::Assign(ExpressionKind::Ident("MAX".to_string()).into(),ExpressionKind::Int(1).into()));
+}
+#[test]
+fn test_parser_014(){
+    let stem = parser_file("./src/script/14_parser.kz");
+    match &stem[0].kind {
+        StatementKind::Import(i) => {
+            assert_eq!(i.file(),"lib/math.kz");
+            assert!(i.names.is_empty());
+        },
+        _ => panic!("expected import")
+    }
+    match &stem[1].kind {
+        StatementKind::Import(i) => {
+            assert_eq!(i.file(),"math.kz");
+            assert_eq!(i.names,vec!["sqrt","pow"]);
+        },
+        _ => panic!("expected import")
+    }
+    match &stem[2].kind {
+        StatementKind::Import(i) => assert_eq!(i.file(),"lib/util.kz"),
+        _ => panic!("expected import")
+    }
+    assert!(matches!(&stem[3].kind,StatementKind::Pub(s) if matches!(s.kind,StatementKind::Fun(_))));
+    assert!(matches!(&stem[4].kind,StatementKind::Pub(s) if matches!(s.kind,StatementKind::Type(_))));
+    assert!(matches!(&stem[5].kind,StatementKind::Pub(s) if matches!(s.kind,StatementKind::Const(_))));
+}
+#[test]
+fn test_parser_015(){
+    let stem = parser_file("./src/script/15_parser.kz");
+    match &stem[0].kind {
+        StatementKind::Fun(f) => {
+            let params:Vec<&ParamExpression> = f.param_exp.iter().flatten().map(|p| match &p.kind {
+                ExpressionKind::Param(p) => p.as_ref(),
+                _ => panic!("expected param")
+            }).collect();
+            assert_eq!(params[1].default,Some(ExpressionKind::Int(1).into()));
+            assert!(params[2].variadic);
+            assert_eq!(params[2].token_type,Token::Array(0,Box::new(Token::String)));
+        },
+        _ => panic!("expected fun")
+    }
+    assert_eq!(stem[1].kind,StatementKind::Invoke(ExpressionKind::Call(Box::new(ExpressionKind::Ident("log".to_string()).into()),vec![
+        ExpressionKind::String("x".to_string()).into(),
+        ExpressionKind::NamedArg("level".to_string(),Box::new(ExpressionKind::Int(3).into())).into(),
+    ]).into()));
+    assert!(matches!(&stem[2].kind,StatementKind::Invoke(Expression { kind:ExpressionKind::Call(_,args), .. }) if args.len() == 4));
+}
+#[test]
+fn test_parser_016(){
+    let stem = parser_file("./src/script/16_parser.kz");
+    let lambda = |stem:&Statement| -> (Option<Token>,usize,bool) {
+        match &stem.kind {
+            StatementKind::Let(l) => match &l.exp {
+                Some(Expression { kind:ExpressionKind::Fun(f), .. }) => (f.ret_type.clone(),f.param_number,f.lambda),
+                _ => panic!("expected lambda")
+            },
+            _ => panic!("expected let")
+        }
+    };
+    assert_eq!(lambda(&stem[2]),(Some(Token::Unknown),1,true));
+    assert_eq!(lambda(&stem[3]),(Some(Token::Bool),2,true));
+    assert_eq!(lambda(&stem[4]),(Some(Token::Int),2,true));
+    assert_eq!(lambda(&stem[5]),(Some(Token::String),0,true));
+}
+#[test]
+fn test_parser_017(){
+    let stem = parser_file("./src/script/17_parser.kz");
+    match &stem[0].kind {
+        StatementKind::Fun(f) => {
+            let call = ExpressionKind::Call(Box::new(ExpressionKind::Ident("open".to_string()).into()),vec![ExpressionKind::Ident("path".to_string()).into()]);
+            let try_call:Expression = ExpressionKind::Try(Box::new(call.into())).into();
+            assert!(matches!(&f.body[0].kind,StatementKind::Let(l) if l.exp == Some(try_call)));
+        },
+        _ => panic!("expected fun")
+    }
+    match &stem[1].kind {
+        StatementKind::Fun(f) => assert!(matches!(&f.body[0].kind,StatementKind::Invoke(Expression { kind:ExpressionKind::Try(_), .. }))),
+        _ => panic!("expected fun")
+    }
+}
+#[test]
+fn test_parser_018(){
+    let stem = parser_file("./src/script/18_parser.kz");
+    match &stem[0].kind {
+        StatementKind::Fun(f) => {
+            assert_eq!(f.body.len(),5);
+            let deferred = Statement::deferred(&f.body);
+            assert_eq!(deferred.len(),2);
+            assert_eq!(deferred[0].len(),2);
+            let close:Statement = StatementKind::Invoke(ExpressionKind::Call(
+                Box::new(ExpressionKind::Ident("close".to_string()).into()),vec![ExpressionKind::Ident("f".to_string()).into()]).into()).into();
+            assert_eq!(deferred[1],&[close][..]);
+        },
+        _ => panic!("expected fun")
+    }
+}
+#[test]
+fn test_parser_019(){
+    let f = PaserFile::new("./src/script/19_parser.kz");
+    let (stem,errors) = Parser::new(Lexer::new(f)).program_recover();
+    let errors:Vec<&KzErr> = errors.iter().map(|e| e.err()).collect();
+    assert_eq!(errors,vec![
+        &KzErr::ExpectedName,
+        &KzErr::UnExpSymbol(Token::N),
+        &KzErr::UnExpSymbol(Token::RightBracket),
+        &KzErr::UnExpSymbol(Token::LeftCurlyBracket),
+    ]);
+    assert_eq!(stem.len(),6);
+    assert_eq!(stem[1].kind,StatementKind::Error);
+    match &stem[2].kind {
+        StatementKind::Fun(f) => {
+            assert_eq!(f.body[0].kind,StatementKind::Error);
+            assert!(matches!(f.body[1].kind,StatementKind::Return(_)));
+        },
+        _ => panic!("expected fun")
+    }
+    assert_eq!(stem[3].kind,StatementKind::Error);
+    assert_eq!(stem[4].kind,StatementKind::Error);
+    assert!(matches!(&stem[5].kind,StatementKind::Let(l) if l.ident == "e"));
+}
+#[test]
+fn test_parser_020(){
+    let stem = parser_file("./src/script/20_parser.kz");
+    assert_eq!(stem.len(),5);
+    match &stem[0].kind {
+        StatementKind::Fun(f) => {
+            assert_eq!(f.doc,Some("Adds two numbers.\nBoth must be ints.".to_string()));
+            assert_eq!(f.body.len(),1);
+        },
+        _ => panic!("expected fun")
+    }
+    match &stem[1].kind {
+        StatementKind::Pub(s) => match &s.kind {
+            StatementKind::Type(t) => assert_eq!(t.doc,Some("the origin".to_string())),
+            _ => panic!("expected type")
+        },
+        _ => panic!("expected pub")
+    }
+    match &stem[2].kind {
+        StatementKind::Let(l) => {
+            assert_eq!(l.doc,None);
+            assert!(matches!(&l.exp,Some(Expression { kind:ExpressionKind::Infix(_), .. })));
+        },
+        _ => panic!("expected let")
+    }
+    assert!(matches!(&stem[3].kind,StatementKind::Const(c) if c.doc == Some("max value".to_string())));
+    assert!(matches!(&stem[4].kind,StatementKind::Let(l) if l.doc.is_none()));
+}
+#[test]
+fn test_parser_span(){
+    let source = "let a:int = 1 + 2\nfun add(x:int, y:int) int {\n    return x + y\n}\nadd(a, 3)\n";
+    let stem = Parser::new(Lexer::new(PaserFile::from_bytes("span.kz",source.as_bytes().to_vec()))).program().unwrap();
+    let text = |span:Span| &source[span.start..span.end];
+    assert_eq!(text(stem[0].span),"let a:int = 1 + 2");
+    match &stem[0].kind {
+        StatementKind::Let(l) => assert_eq!(text(l.exp.as_ref().unwrap().span),"1 + 2"),
+        _ => panic!("expected let")
+    }
+    assert_eq!(text(stem[1].span),"fun add(x:int, y:int) int {\n    return x + y\n}");
+    match &stem[1].kind {
+        StatementKind::Fun(f) => {
+            let params:Vec<&str> = f.param_exp.iter().flatten().map(|p| text(p.span)).collect();
+            assert_eq!(params,vec!["x:int","y:int"]);
+            assert_eq!(text(f.body[0].span),"return x + y");
+            assert_eq!((f.body[0].span.line,f.body[0].span.col),(3,5));
+        },
+        _ => panic!("expected fun")
+    }
+    assert_eq!(text(stem[2].span),"add(a, 3)");
+    assert_eq!((stem[2].span.line,stem[2].span.col),(5,1));
+
+    //the same program moved down two lines is still equal, spans aside
+    let moved = format!("\n\n{}",source);
+    let other = Parser::new(Lexer::new(PaserFile::from_bytes("span.kz",moved.into_bytes()))).program().unwrap();
+    assert_eq!(stem,other);
+    assert_ne!(stem[0].span,other[0].span);
+
+    let f = PaserFile::from_bytes("span.kz",b"let a = 1\nlet = 2\n".to_vec());
+    let (stem,errors) = Parser::new(Lexer::new(f)).program_recover();
+    assert_eq!((errors[0].span().line,errors[0].span().col),(2,1));
+    assert_eq!(stem[1].kind,StatementKind::Error);
+    assert_eq!(stem[1].span.line,2);
+}
+#[test]
+fn test_parser_eof(){
+    let cases = [
+        ("let a:[]int = [1,2","']'"),
+        ("fun f(a:int) {\n let b:int = a","'}'"),
+        ("if a == b {\n} else {","'}'"),
+        ("for let i:int = 0;i < 3;i++ {","'}'"),
+        ("type s struct {\n a:int","'}'"),
+        ("let b:int = a[1","']'"),
+        ("let (a,b","')'"),
+        ("add(1,2","')'"),
+        ("let f = |a, b","'|'"),
+        ("let a:(int,string","')'"),
+        ("/** notes","'*/'"),
+        ("/* notes /* nested */","'*/'"),
+        ("let a:int =","an expression"),
+    ];
+    for (source,expected) in cases {
+        let f = PaserFile::from_bytes("eof.kz",source.as_bytes().to_vec());
+        let (_,errors) = Parser::new(Lexer::new(f)).program_recover();
+        let errors:Vec<&KzErr> = errors.iter().map(|e| e.err()).collect();
+        assert_eq!(errors,vec![&KzErr::Eof(expected.to_string())],"{}",source);
+    }
+}
+//every prefix of every fixture must come back with a result, never hang or panic
+#[test]
+fn test_parser_truncated(){
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let mut paths:Vec<_> = std::fs::read_dir("./src/script").unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "kz"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+    for path in paths {
+        let name = path.to_string_lossy().to_string();
+        let body = std::fs::read(&path).unwrap();
+        let (tx,rx) = mpsc::channel();
+        let len = body.len();
+        let file = name.clone();
+        std::thread::spawn(move || {
+            //after each parse, the untruncated file last
+            for end in 0..=body.len() {
+                let f = PaserFile::from_bytes(&file,body[..end].to_vec());
+                Parser::new(Lexer::new(f)).program_recover();
+                tx.send(end + 1).unwrap();
+            }
+        });
+        //parses that returned, the one truncated at done bytes is running
+        let mut done = 0;
+        while done <= len {
+            match rx.recv_timeout(Duration::from_secs(5)) {
+                Ok(end) => done = end,
+                Err(mpsc::RecvTimeoutError::Timeout) => panic!("{} truncated at {} bytes never returned",name,done),
+                Err(mpsc::RecvTimeoutError::Disconnected) => panic!("{} truncated at {} bytes panicked",name,done)
+            }
+        }
+    }
+}
+#[cfg(test)]
+fn parser_file(s:&str) -> Vec<Statement> {
+    let f = PaserFile::new(s);
+    let mut p = Parser::new(Lexer::new(f));
+    p.program().unwrap()
+}
+#[test]
+fn test_parser_expression(){
+    fn infix(exp:&Expression) -> &InfixExpression {
+        match &exp.kind {
+            ExpressionKind::Infix(infix) => infix,
+            kind => panic!("expected infix, got {}",kind.name())
+        }
+    }
+    let source = "user.age > 18 && region == \"cn\"";
+    let exp = Parser::parse_expression(source).unwrap();
+    assert_eq!((exp.span.start,exp.span.end),(0,source.len()));
+    let and = infix(&exp);
+    assert_eq!(and.op_symbol,Token::And);
+    let gt = infix(&and.left);
+    assert_eq!(gt.op_symbol,Token::GT);
+    assert_eq!(gt.left.kind,ExpressionKind::Field(Box::new(ExpressionKind::Ident("user".to_string()).into()),"age".to_string()));
+    assert_eq!((gt.left.span.start,gt.left.span.end),(0,8));
+    let equ = infix(&and.right);
+    assert_eq!(equ.op_symbol,Token::Equ);
+    assert_eq!(equ.right.kind,ExpressionKind::String("cn".to_string()));
+
+    //comparison binds looser than arithmetic, || looser than &&
+    let exp = Parser::parse_expression("\n a + 1 > b * 2 || c && d\n").unwrap();
+    let or = infix(&exp);
+    assert_eq!(or.op_symbol,Token::Or);
+    assert_eq!(infix(&or.right).op_symbol,Token::And);
+    let gt = infix(&or.left);
+    assert_eq!(gt.op_symbol,Token::GT);
+    assert_eq!(infix(&gt.left).op_symbol,Token::Add);
+    assert_eq!(infix(&gt.right).op_symbol,Token::Mul);
+    assert_eq!((exp.span.line,exp.span.col),(2,2));
+
+    //errors point into the snippet
+    let err = Parser::parse_expression("a + 1 b").unwrap_err();
+    assert_eq!(err.err(),&KzErr::UnExpSymbol(Token::Ident("b".to_string())));
+    assert_eq!((err.span().line,err.span().col,err.span().start),(1,7,6));
+    let err = Parser::parse_expression("a +").unwrap_err();
+    assert_eq!(err.err(),&KzErr::Eof("an expression".to_string()));
+    assert!(Parser::parse_expression("|x| { x } y").is_err());
+    assert!(Parser::parse_expression("|x| { x }").is_ok());
+
+    assert_eq!(Parser::parse_type("[]?string").unwrap(),Token::Array(0,Box::new(Token::Optional(Box::new(Token::String)))));
+    assert_eq!(Parser::parse_type("(int,string)").unwrap(),Token::Tuple(vec![Token::Int,Token::String]));
+    let err = Parser::parse_type("int x").unwrap_err();
+    assert_eq!(err.err(),&KzErr::UnExpSymbol(Token::Ident("x".to_string())));
+    assert_eq!(err.span().col,5);
+    assert!(Parser::parse_type("1").is_err());
+}
+#[test]
+fn test_parser_float_range(){
+    let big = format!("{}9.0","9".repeat(40));
+    assert_eq!(Parser::parse_expression(&big).unwrap_err().err(),&KzErr::Value(big.clone()));
+    assert_eq!(Parser::parse_expression("3.5").unwrap().kind,ExpressionKind::Float(3.5));
+}
+#[test]
+fn test_parser_let_without_value(){
+    let program = crate::parse_str("let x:int\nif true {\n    let y }\nlet z:int 1\n");
+    let lets:Vec<(&str,bool)> = program.statements.iter().filter_map(|stem| match &stem.kind {
+        StatementKind::Let(l) => Some((l.ident.as_str(),l.exp.is_some())),
+        _ => None
+    }).collect();
+    assert_eq!(lets,vec![("x",false)]);
+    assert_eq!(program.source_map.text(program.statements[0].span),"let x:int");
+    let errs:Vec<&KzErr> = program.diagnostics.iter().map(|e| e.err()).filter(|e| e.severity() == crate::parser::error::Severity::Error).collect();
+    assert_eq!(errs,vec![&KzErr::Expected(Token::Assign)]);
+}
@@ -0,0 +1,61 @@
+//byte range of a token or node in its source file, start inclusive end exclusive,
+//line and col are 1-based and point at start
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash,Default)]
+pub struct Span {
+    pub file_id:usize,
+    pub start:usize,
+    pub end:usize,
+    pub line:i32,
+    pub col:i32,
+}
+
+impl Span {
+    //from the start of self to the end of other
+    pub fn to(&self,other:Span) -> Span {
+        Span {
+            end:other.end.max(self.start),
+            ..*self
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+pub struct SourceFile {
+    pub path:String,
+    pub source:Vec<u8>,
+}
+
+//the files spans point into, indexed by Span::file_id
+#[derive(Default)]
+pub struct SourceMap {
+    files:Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        Self::default()
+    }
+    //returns the file_id for the spans of this file
+    pub fn add(&mut self,path:&str,source:Vec<u8>) -> usize {
+        self.files.push(SourceFile {
+            path:path.to_string(),
+            source,
+        });
+        self.files.len() - 1
+    }
+    pub fn file(&self,file_id:usize) -> Option<&SourceFile> {
+        self.files.get(file_id)
+    }
+    //the source a span covers, empty when it does not point into a known file
+    pub fn text(&self,span:Span) -> &str {
+        self.file(span.file_id)
+            .and_then(|f| f.source.get(span.start..span.end))
+            .and_then(|bytes| std::str::from_utf8(bytes).ok())
+            .unwrap_or("")
+    }
+}
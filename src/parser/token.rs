@@ -10,6 +10,8 @@ pub enum Token {
     If,
     Else,
     Unknown,
+    //what a fun without a return type gives, only used by analysis::types
+    Void,
     Basics(String,Box<Token>),
     Sem,
     N,
@@ -41,24 +43,35 @@ pub enum Token {
     LeftSquareBra,
     RightSquareBra,
     Array(i32,Box<Token>),
+    Tuple(Vec<Token>),
+    Optional(Box<Token>),
+    Question,
+    Nil,
+    Const,
+    Import,
+    Pub,
+    Dot,
+    Ellipsis,
+    Pipe,
+    FatArrow,
+    And,
+    Or,
+    Defer,
     For,
     SelfSub,
     SelfAdd,
     Break,
     Continue,
     Return,
+    //a whole comment: // ...  /* ... */ (nested), /** ... **/
     Notes,
     NotesBlock,
-    NotesBlockEnd,
+    //  /// text, attached to the following let, fun or type
+    DocNotes(String),
 }
 impl Token {
     pub fn let_type(tok:&Token) -> bool {
-         match tok {
-             Token::Int | Token::String | Token::Float  => true,
-             Token::Ident(_) => true,
-             Token::Array(_,_) => true,
-             _ => false
-         }
+         matches!(tok,Token::Int | Token::String | Token::Float | Token::Ident(_) | Token::Array(_,_) | Token::Tuple(_) | Token::Optional(_))
     }
 }
 //if else for true false
@@ -77,6 +90,7 @@ impl Debug for Token {
              Token::If => "if",
              Token::Else => "else",
              Token::Unknown => "unknown",
+             Token::Void => "void",
              Token::Sem => "sem",
              Token::N => "<N>",
              Token::Err(val) => {
@@ -118,6 +132,26 @@ impl Debug for Token {
                  s = format!("[{}]{:?}",number,tok);
                  s.as_str()
              },
+             Token::Tuple(toks) => {
+                 s = format!("({})",toks.iter().map(|t| format!("{:?}",t)).collect::<Vec<String>>().join(","));
+                 s.as_str()
+             },
+             Token::Optional(tok) => {
+                 s = format!("?{:?}",tok);
+                 s.as_str()
+             },
+             Token::Question => "?",
+             Token::Nil => "nil",
+             Token::Const => "const",
+             Token::Import => "import",
+             Token::Pub => "pub",
+             Token::Dot => ".",
+             Token::Ellipsis => "...",
+             Token::Pipe => "|",
+             Token::FatArrow => "=>",
+             Token::And => "&&",
+             Token::Or => "||",
+             Token::Defer => "defer",
              Token::For => "for",
              Token::SelfSub => "--",
              Token::SelfAdd => "++",
@@ -125,19 +159,21 @@ impl Debug for Token {
              Token::Continue => "continue",
              Token::Return => "return",
              Token::Notes => "//",
-             Token::NotesBlock => "/**",
-             Token::NotesBlockEnd => "**/"
+             Token::NotesBlock => "/*",
+             Token::DocNotes(_) => "///",
          })
     }
 }
 #[derive(PartialOrd,PartialEq)]
 pub enum Operation {
     Lowest,
-    Index,
+    Or,
+    And,
     EquAls,
+    LtAndGt,
     AddAndSub,
     MulAndDiv,
-    LtAndGt,
+    Index,
     Call
 }
 
@@ -0,0 +1,80 @@
+use crate::analysis;
+use crate::analysis::cfg;
+use crate::analysis::resolve::SymbolTable;
+use crate::analysis::types::TypeTable;
+use crate::ast::export;
+use crate::ast::node::Statement;
+use crate::parser::error::{KzError, Severity};
+use crate::parser::file::PaserFile;
+use crate::parser::lexer::Lexer;
+use crate::parser::parse::Parser;
+use crate::parser::span::SourceMap;
+
+//a parsed script, statements that failed to parse are kept as StatementKind::Error
+//and their errors are in diagnostics next to the ones found by analysis::check
+pub struct Program {
+    pub statements:Vec<Statement>,
+    pub source_map:SourceMap,
+    pub diagnostics:Vec<KzError>,
+    //declarations and what every identifier refers to, see analysis::resolve
+    pub symbols:SymbolTable,
+    //declared and inferred type of every node, see analysis::types
+    pub types:TypeTable,
+}
+
+impl Program {
+    pub(crate) fn parse(mut f:PaserFile) -> Program {
+        let mut source_map = SourceMap::new();
+        f.id = source_map.add(f.file_path(),f.body.clone());
+        let path = f.file_path().to_string();
+        let (mut statements,mut diagnostics) = Parser::new(Lexer::new(f)).program_recover();
+        diagnostics.extend(analysis::check(&path,&statements));
+        let (symbols,errs) = analysis::resolve::resolve(&path,&statements);
+        diagnostics.extend(errs);
+        analysis::capture::resolve(&mut statements,&symbols);
+        let (types,errs) = analysis::types::infer(&path,&statements,&symbols);
+        diagnostics.extend(errs);
+        diagnostics.extend(analysis::dataflow::check(&path,&statements,&symbols));
+        Program {
+            statements,
+            source_map,
+            diagnostics,
+            symbols,
+            types,
+        }
+    }
+    //no errors, warnings are allowed
+    pub fn is_ok(&self) -> bool {
+        self.diagnostics.iter().all(|e| e.severity() == Severity::Warning)
+    }
+    //the tree and diagnostics with spans, the schema is described in ast::export
+    pub fn to_json(&self) -> String {
+        export::json(&self.statements,&self.diagnostics)
+    }
+    //the tree without spans, one statement per line
+    pub fn to_sexp(&self) -> String {
+        export::sexp(&self.statements)
+    }
+    //the control flow graph of the script and of every fun in it, see analysis::cfg
+    pub fn to_dot(&self) -> String {
+        cfg::functions(&self.statements).iter().map(|(name,graph)| graph.dot(name)).collect()
+    }
+}
+
+#[test]
+fn test_program() {
+    let program = crate::parse_str("let a:int = 1\nfun f() int {\n    return a\n}\n");
+    assert!(program.is_ok());
+    assert_eq!(program.statements.len(),2);
+    assert_eq!(program.source_map.text(program.statements[0].span),"let a:int = 1");
+
+    let program = crate::parse_str("let a:int = 1\nlet = 2\nconst B:int = a\n");
+    let diagnostics:Vec<String> = program.diagnostics.iter().map(|e| e.to_string()).collect();
+    assert_eq!(diagnostics.len(),2);
+    assert!(diagnostics[0].starts_with("<str>  line->2:1"));
+    assert!(diagnostics[1].starts_with("<str>  line->3:1"));
+
+    let program = crate::parse_file("./src/script/11_parser.kz").unwrap();
+    assert_eq!(program.source_map.file(0).unwrap().path,"./src/script/11_parser.kz");
+    assert!(crate::parse_file("./src/script/missing.kz").is_err());
+}
@@ -0,0 +1,110 @@
+//golden files for the fixtures in src/script: the tree of every fixture as s-expressions
+//(see ast::export) followed by its diagnostics, checked in under snapshots/ next to it.
+//
+//  KZ_BLESS=1 cargo test    rewrites every snapshot from the current output
+//
+//a fixture without a snapshot fails until it is blessed, review the diff before committing
+use std::path::{Path, PathBuf};
+
+use crate::parser::error::{KzErr, KzError};
+use crate::parser::module::ModuleLoader;
+
+fn snapshot_path(fixture:&Path) -> PathBuf {
+    let name = fixture.file_stem().unwrap().to_string_lossy().to_string();
+    fixture.parent().unwrap().join("snapshots").join(format!("{}.snap",name))
+}
+fn diagnostic(e:&KzError) -> String {
+    format!("{}:{} {} {}",e.span().line,e.span().col,e.err().name(),e.err().echo())
+}
+//the parse and check results of a fixture, import errors are only
+//looked for when the file itself has no errors
+pub(crate) fn render(fixture:&str) -> (String,Vec<KzError>) {
+    let program = crate::parse_file(fixture).unwrap();
+    let ok = program.is_ok();
+    let mut diagnostics = program.diagnostics;
+    if ok {
+        if let Err(e) = ModuleLoader::new().load(fixture) {
+            diagnostics.push(e);
+        }
+    }
+    let mut out = crate::ast::export::sexp(&program.statements);
+    out.push('\n');
+    if !diagnostics.is_empty() {
+        out.push_str("---\n");
+        for e in &diagnostics {
+            out.push_str(&diagnostic(e));
+            out.push('\n');
+        }
+    }
+    (out,diagnostics)
+}
+fn check(fixture:&str) {
+    let (actual,_) = render(fixture);
+    let snap = snapshot_path(Path::new(fixture));
+    if std::env::var_os("KZ_BLESS").is_some() {
+        std::fs::create_dir_all(snap.parent().unwrap()).unwrap();
+        std::fs::write(&snap,&actual).unwrap();
+        return
+    }
+    let expected = match std::fs::read_to_string(&snap) {
+        Ok(expected) => expected,
+        Err(_) => panic!("{} has no snapshot, run KZ_BLESS=1 cargo test to create {}",fixture,snap.display())
+    };
+    if expected != actual {
+        let line = expected.lines().zip(actual.lines()).position(|(a,b)| a != b)
+            .unwrap_or(expected.lines().count().min(actual.lines().count()));
+        panic!("{} does not match {} from line {}\nexpected: {}\nactual:   {}\nrun KZ_BLESS=1 cargo test to update it",
+            fixture,snap.display(),line + 1,
+            expected.lines().nth(line).unwrap_or("<end>"),
+            actual.lines().nth(line).unwrap_or("<end>"));
+    }
+}
+pub(crate) fn fixtures(dir:&str) -> Vec<String> {
+    let mut paths:Vec<String> = std::fs::read_dir(dir).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "kz"))
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+    paths
+}
+
+#[test]
+fn test_snapshot_fixtures(){
+    for path in fixtures("./src/script") {
+        check(&path);
+    }
+}
+//src/script/errors holds one fixture per error, const_assign.kz must fail with KzErr::ConstAssign first.
+//more cases of one error go in un_exp_symbol.trailing.kz, the part after the dot is ignored
+#[test]
+fn test_snapshot_errors(){
+    //every token with a precedence has an infix arm in Parser::expression,
+    //so UnOpSymbol is never produced and has no fixture
+    let unreachable = ["UnOpSymbol"];
+    let all = [
+        "Program","Type","Expected","ExpectedName","UnExpSymbol","UnOpSymbol","Value","ParamName",
+        "ConstExpr","ConstAssign","Import","ImportCycle","ImportName","ParamDefault","ParamVariadic",
+        "Try","Defer","Eof","Undefined","Duplicate","UseBeforeDeclaration",
+        "TypeMismatch","Operand","ArgCount","ArgName","Index","NoField","AliasCycle","Destructure","MissingReturn","Unreachable","OutsideLoop",
+        "ReadBeforeAssign","UnusedVariable","DeadStore","UnusedParam",
+    ];
+    let mut covered = Vec::new();
+    for path in fixtures("./src/script/errors") {
+        check(&path);
+        let (_,diagnostics) = render(&path);
+        let stem = Path::new(&path).file_stem().unwrap().to_string_lossy().to_string();
+        let name:String = stem.split('.').next().unwrap().split('_').map(|part| {
+            let mut chars = part.chars();
+            chars.next().map(|c| c.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+        }).collect();
+        assert!(!diagnostics.is_empty(),"{} has no errors",path);
+        assert_eq!(diagnostics[0].err().name(),name,"{}",path);
+        covered.push(name);
+    }
+    for name in all {
+        assert!(covered.iter().any(|c| c == name) || unreachable.contains(&name),"no fixture for KzErr::{}",name);
+    }
+    assert_eq!(KzErr::Defer.name(),"Defer");
+}